@@ -1,7 +1,7 @@
 use itertools::Itertools;
 use quick_xml::de::from_reader;
 use serde::Deserialize;
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs::File;
 use std::io::Write;
 
@@ -17,6 +17,32 @@ pub fn main() {
         .map(|s| (s.id.clone(), s))
         .collect();
 
+    let lemma_by_entry: HashMap<_, _> = resource
+        .lexicon
+        .lexical_entries
+        .iter()
+        .map(|e| (e.id.clone(), e.lemma.written_form.clone()))
+        .collect();
+
+    let represented_synsets: HashSet<_> = resource
+        .lexicon
+        .lexical_entries
+        .iter()
+        .filter(|e| e.lemma.is_dictionary_word())
+        .flat_map(|e| e.senses.iter().map(|s| s.synset.clone()))
+        .collect();
+
+    let lemma_by_sense: HashMap<_, _> = resource
+        .lexicon
+        .lexical_entries
+        .iter()
+        .flat_map(|e| {
+            e.senses
+                .iter()
+                .map(|s| (s.id.clone(), e.lemma.written_form.clone()))
+        })
+        .collect();
+
     let words_path = "src/core/WordData.tsv";
 
     let mut words_output = File::create(words_path).expect("Could not open file for writing");
@@ -31,6 +57,12 @@ pub fn main() {
         .map(|x| x.to_ascii_lowercase())
         .collect();
 
+    let emoji_reader =
+        quick_xml::Reader::from_file("src\\bin\\dict-generator\\emoji-annotations.xml")
+            .expect("Could not read CLDR emoji annotations file. You may need to download this");
+    let emoji_annotations: Ldml = from_reader(emoji_reader.into_inner()).unwrap();
+    let emoji_by_word = emoji_annotations.annotations.emoji_by_word();
+
     let mut words = resource
         .lexicon
         .lexical_entries
@@ -43,6 +75,52 @@ pub fn main() {
             } else if negative_words.contains(&e.lemma.written_form.to_ascii_lowercase()) {
                 tags_vec.push("negative")
             }
+            let emoji = emoji_by_word
+                .get(&e.lemma.written_form.to_ascii_lowercase())
+                .cloned()
+                .unwrap_or_default();
+            let synsets = e
+                .senses
+                .iter()
+                .map(|s| short_synset_id(&s.synset))
+                .unique()
+                .join(" ");
+            let antonyms = e
+                .senses
+                .iter()
+                .flat_map(|s| s.sense_relations.iter())
+                .filter(|r| r.rel_type == "antonym")
+                .filter_map(|r| lemma_by_sense.get(&r.target))
+                .filter(|l| !l.contains(' '))
+                .unique()
+                .join(" ");
+            let similar = e
+                .senses
+                .iter()
+                .flat_map(|s| synset_dic[&s.synset].synset_relations.iter())
+                .filter(|r| r.rel_type == "similar")
+                .flat_map(|r| synset_dic[&r.target].members.split_ascii_whitespace())
+                .filter_map(|m| lemma_by_entry.get(m))
+                .filter(|l| !l.contains(' ') && **l != e.lemma.written_form)
+                .unique()
+                .join(" ");
+            let hypernyms = e
+                .senses
+                .iter()
+                .map(|s| &s.synset)
+                .unique()
+                .filter_map(|synset| {
+                    let parents = represented_hypernyms(synset, &synset_dic, &represented_synsets);
+                    if parents.is_empty() {
+                        return None;
+                    }
+                    Some(format!(
+                        "{}:{}",
+                        short_synset_id(synset),
+                        parents.iter().map(|p| short_synset_id(p)).join(",")
+                    ))
+                })
+                .join(" ");
             Word {
                 part_of_speech: e.lemma.part_of_speech,
                 lemma: e.lemma.written_form,
@@ -53,6 +131,19 @@ pub fn main() {
                     .next()
                     .unwrap_or_default(),
                 tags: tags_vec.join(" "),
+                emoji,
+                synsets,
+                antonyms,
+                similar,
+                hypernyms,
+                pronunciation: e
+                    .lemma
+                    .pronunciations
+                    .iter()
+                    .map(|p| p.text.trim())
+                    .filter(|p| !p.is_empty())
+                    .unique()
+                    .join(" "),
             }
         })
         .collect_vec();
@@ -67,6 +158,12 @@ pub fn main() {
             lemma: name.to_string(),
             definition: "".to_string(),
             tags: "masculine".to_string(),
+            pronunciation: "".to_string(),
+            emoji: "".to_string(),
+            synsets: "".to_string(),
+            antonyms: "".to_string(),
+            similar: "".to_string(),
+            hypernyms: "".to_string(),
         })
         .take(1000)
         .interleave(girls_names.map(|name| Word {
@@ -74,6 +171,12 @@ pub fn main() {
             lemma: name.to_string(),
             definition: "".to_string(),
             tags: "feminine".to_string(),
+            pronunciation: "".to_string(),
+            emoji: "".to_string(),
+            synsets: "".to_string(),
+            antonyms: "".to_string(),
+            similar: "".to_string(),
+            hypernyms: "".to_string(),
         }))
         .take(1000);
 
@@ -87,6 +190,12 @@ pub fn main() {
             lemma: name.to_string(),
             definition: "".to_string(),
             tags: "".to_string(),
+            pronunciation: "".to_string(),
+            emoji: "".to_string(),
+            synsets: "".to_string(),
+            antonyms: "".to_string(),
+            similar: "".to_string(),
+            hypernyms: "".to_string(),
         });
 
     words.extend(last_names);
@@ -94,11 +203,17 @@ pub fn main() {
     for word in words {
         writeln!(
             words_output,
-            "{}\t{}\t{}\t{}",
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
             word.part_of_speech.to_str(),
             word.lemma,
             word.definition,
-            word.tags
+            word.tags,
+            word.pronunciation,
+            word.emoji,
+            word.synsets,
+            word.antonyms,
+            word.similar,
+            word.hypernyms
         )
         .expect("Could not write line");
     }
@@ -251,4 +366,104 @@ pub struct Word {
     pub lemma: String,
     pub definition: String,
     pub tags: String,
+    /// IPA pronunciations, separated by spaces
+    pub pronunciation: String,
+    pub emoji: String,
+    /// Synset ids, separated by spaces
+    pub synsets: String,
+    /// Antonyms, separated by spaces
+    pub antonyms: String,
+    /// Members of similar synsets, separated by spaces
+    pub similar: String,
+    /// Each synset followed by the synsets above it, such as `02:04,05 03:06`
+    pub hypernyms: String,
+}
+
+/// Synset ids without the prefix shared by every id
+fn short_synset_id(id: &str) -> &str {
+    id.trim_start_matches("oewn-")
+}
+
+/// The nearest synsets above this one which have a word in the output.
+/// Synsets without any words are skipped so that every ancestor with words is still reachable.
+fn represented_hypernyms<'a>(
+    synset: &str,
+    synset_dic: &HashMap<String, &'a Synset>,
+    represented: &HashSet<String>,
+) -> BTreeSet<&'a str> {
+    let mut result = BTreeSet::new();
+    let mut visited = HashSet::new();
+    let mut stack = vec![synset.to_string()];
+
+    while let Some(current) = stack.pop() {
+        let Some(current) = synset_dic.get(&current) else {
+            continue;
+        };
+
+        for relation in current
+            .synset_relations
+            .iter()
+            .filter(|r| r.rel_type == "hypernym" || r.rel_type == "instance_hypernym")
+        {
+            if !visited.insert(relation.target.clone()) {
+                continue;
+            }
+            if represented.contains(&relation.target) {
+                result.insert(synset_dic[&relation.target].id.as_str());
+            } else {
+                stack.push(relation.target.clone());
+            }
+        }
+    }
+
+    result
+}
+
+/// A CLDR annotations file, such as common/annotations/en.xml
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct Ldml {
+    #[serde(default)]
+    pub annotations: Annotations,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct Annotations {
+    #[serde(rename = "annotation", default)]
+    pub annotations: Vec<Annotation>,
+}
+
+impl Annotations {
+    /// The emoji for each word.
+    /// Words which are the whole name of an emoji come first, then keywords, keeping the first emoji for each word.
+    pub fn emoji_by_word(&self) -> HashMap<String, String> {
+        let (names, keywords): (Vec<_>, Vec<_>) = self
+            .annotations
+            .iter()
+            .partition(|a| a.annotation_type.as_deref() == Some("tts"));
+
+        let mut result = HashMap::new();
+
+        for annotation in names.into_iter().chain(keywords) {
+            for word in annotation
+                .text
+                .split('|')
+                .map(|x| x.trim().to_ascii_lowercase())
+            {
+                if !word.is_empty() && !word.contains(' ') {
+                    result.entry(word).or_insert_with(|| annotation.cp.clone());
+                }
+            }
+        }
+
+        result
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct Annotation {
+    pub cp: String,
+    #[serde(rename = "type", default)]
+    pub annotation_type: Option<String>,
+    #[serde(rename = "$value")]
+    pub text: String,
 }
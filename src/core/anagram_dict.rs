@@ -44,16 +44,34 @@ impl AnagramDict {
     ) -> impl '_ + Iterator<Item = ExpressionSolution> {
         let iterator = AnagramIterator::<4>::create(self, key, settings);
 
-        iterator.flat_map(|solution| {
-            solution
-                .into_iter()
-                .map(|k| self.words.get(&k).unwrap().clone()) //Note if terms with the same text, they will each be returned
-                .multi_cartesian_product()
-                .map(|x| ExpressionSolution {
-                    homographs: SmallVec::from_vec(x),
-                })
+        iterator.flat_map(|solution| self.expand_keys(solution))
+    }
+
+    /// Find every combination of words which uses some of the letters of the key, along with the unused letters
+    pub fn solve_partial(
+        &self,
+        key: AnagramKey,
+        settings: AnagramSettings,
+    ) -> impl '_ + Iterator<Item = (ExpressionSolution, AnagramKey)> {
+        let iterator = SubAnagramIterator::<4>::create(self, key, settings);
+
+        iterator.flat_map(|(solution, remainder)| {
+            self.expand_keys(solution)
+                .map(move |expression| (expression, remainder))
         })
     }
+
+    fn expand_keys(
+        &self,
+        keys: SmallVec<[AnagramKey; 4]>,
+    ) -> impl '_ + Iterator<Item = ExpressionSolution> {
+        keys.into_iter()
+            .map(|k| self.words.get(&k).unwrap().clone()) //Note if terms with the same text, they will each be returned
+            .multi_cartesian_product()
+            .map(|x| ExpressionSolution {
+                homographs: SmallVec::from_vec(x),
+            })
+    }
 }
 
 #[cfg(test)]
@@ -65,6 +83,7 @@ mod tests {
 
     use crate::core::prelude::*;
     use ntest::test_case;
+    use std::str::FromStr;
 
     #[test]
     fn test_solve_with_term_dict() {
@@ -154,4 +173,38 @@ mod tests {
 
         assert_eq!(solutions_string, expect);
     }
+
+    #[test_case(
+        "i react",
+        "act ire cat tic",
+        1,
+        "act; cat; ire; tic; act ire; cat ire",
+        name = "one_or_two_words"
+    )]
+    #[test_case("cat", "act cat", 1, "act; cat", name = "whole_word")]
+    #[test_case("dog", "act cat", 1, "", name = "no_words")]
+    fn test_solve_partial(input: &str, terms: &str, min_word_length: u8, expect: &str) {
+        let words = terms.split_ascii_whitespace().map(|text| Homograph {
+            text: text.into(),
+            is_single_word: true,
+            meanings: Default::default(),
+        });
+
+        let dict = AnagramDict::from(words);
+
+        let solutions = dict.solve_partial(
+            AnagramKey::from_str(input).unwrap(),
+            AnagramSettings {
+                min_word_length,
+                max_words: Some(2),
+            },
+        );
+
+        let solutions_string = solutions
+            .map(|(s, _)| s.get_text())
+            .sorted_by_key(|x| (x.len(), x.clone()))
+            .join("; ");
+
+        assert_eq!(solutions_string, expect);
+    }
 }
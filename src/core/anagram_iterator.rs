@@ -13,7 +13,13 @@ pub struct AnagramIterator<'b, const N: usize> {
 impl<'b, const N: usize> AnagramIterator<'b, N> {
     pub fn create(dict: &'b AnagramDict, key: AnagramKey, settings: AnagramSettings) -> Self {
         let mut stack = SmallVec::<[(AnagramKey, Bound<AnagramKey>); N]>::new();
-        stack.push((key, Bound::Included(key)));
+        //Words using blanks can come after the key
+        let bound = if key.blanks() > 0 {
+            Bound::Unbounded
+        } else {
+            Bound::Included(key)
+        };
+        stack.push((key, bound));
 
         Self {
             dict,
@@ -52,7 +58,9 @@ impl<'b, const N: usize> Iterator for AnagramIterator<'b, N> {
                     new_used.push(next_key);
                     return Some(new_used);
                 } else if self.settings.allow_key(&remainder) {
-                    if self.settings.max_words == Some(self.used_words.len() + 2) {
+                    if remainder.blanks() == 0
+                        && self.settings.max_words == Some(self.used_words.len() + 2)
+                    {
                         if remainder <= next_key && self.dict.words.contains_key(&remainder) {
                             //if(self.settings.allow_word(l))
                             {
@@ -63,7 +71,7 @@ impl<'b, const N: usize> Iterator for AnagramIterator<'b, N> {
                             }
                         }
                     } else if self.settings.max_words.is_none()
-                        || self.settings.max_words > Some(self.used_words.len() + 2)
+                        || self.settings.max_words >= Some(self.used_words.len() + 2)
                     {
                         self.used_words.push(next_key);
                         self.stack.push((remainder, Bound::Included(next_key)))
@@ -4,24 +4,51 @@ use std::{
 };
 
 use anyhow::anyhow;
+use auto_enums::auto_enum;
+use itertools::Itertools;
 use prime_bag::PrimeBag128;
+use serde::Serialize;
+use strum::IntoEnumIterator;
 
 use super::prelude::Character;
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub struct AnagramKey {
+    /// The number of letters, including blanks
     len: u8,
+    /// The number of blanks, which can stand for any letter
+    blanks: u8,
     inner: prime_bag::PrimeBag128<Character>,
 }
 
 impl AnagramKey {
     pub fn is_empty(&self) -> bool {
-        self.inner.is_empty()
+        self.inner.is_empty() && self.blanks == 0
+    }
+
+    /// The number of letters in this key, including blanks
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// The number of blanks in this key
+    pub fn blanks(&self) -> usize {
+        self.blanks as usize
+    }
+
+    /// This key with the blanks removed
+    pub fn without_blanks(self) -> Self {
+        Self {
+            len: self.len - self.blanks,
+            blanks: 0,
+            inner: self.inner,
+        }
     }
 
     pub fn empty() -> Self {
         Self {
             len: 0,
+            blanks: 0,
             inner: Default::default(),
         }
     }
@@ -33,19 +60,86 @@ impl AnagramKey {
     pub fn try_add(self, rhs: Self) -> Option<Self> {
         let inner = self.inner.try_sum(&rhs.inner)?;
         let len = self.len + rhs.len;
-        AnagramKey { inner, len }.into()
+        let blanks = self.blanks + rhs.blanks;
+        AnagramKey { inner, len, blanks }.into()
     }
 
+    /// Remove the letters of `rhs` from this key.
+    /// Blanks are used for any letters which this key does not have.
     pub fn try_sub(self, rhs: Self) -> Option<Self> {
-        let inner = self.inner.try_difference(&rhs.inner)?;
-        let len = self.len - rhs.len;
-        AnagramKey { inner, len }.into()
+        let blanks = self.blanks.checked_sub(rhs.blanks)?;
+        let len = self.len.checked_sub(rhs.len)?;
+
+        if let Some(inner) = self.inner.try_difference(&rhs.inner) {
+            return AnagramKey { inner, len, blanks }.into();
+        }
+        if blanks == 0 {
+            return None;
+        }
+
+        let common = self.inner.intersection(&rhs.inner);
+        let missing = rhs.inner.try_difference(&common)?.into_iter().count();
+        let blanks = blanks.checked_sub(missing.try_into().ok()?)?;
+        let inner = self.inner.try_difference(&common)?;
+
+        AnagramKey { inner, len, blanks }.into()
+    }
+
+    /// Every key made of exactly `length` letters, without blanks.
+    /// There are none if that many of the last letter would not fit in a key.
+    #[auto_enum(Iterator)]
+    pub fn all_of_length(length: usize) -> impl Iterator<Item = Self> {
+        let last = std::iter::repeat_n(Character::Z, length);
+        if u8::try_from(length).is_err() || PrimeBag128::try_from_iter(last).is_none() {
+            std::iter::empty()
+        } else {
+            Character::iter()
+                .combinations_with_replacement(length)
+                .filter_map(move |characters| {
+                    Some(AnagramKey {
+                        inner: PrimeBag128::try_from_iter(characters)?,
+                        len: length as u8,
+                        blanks: 0,
+                    })
+                })
+        }
+    }
+
+    /// Every distinct key made of exactly `length` of the letters in this key, ignoring blanks
+    pub fn sub_keys_of_length(self, length: usize) -> impl Iterator<Item = Self> {
+        self.characters()
+            .sorted()
+            .combinations(length)
+            .unique()
+            .filter_map(move |characters| {
+                Some(AnagramKey {
+                    inner: PrimeBag128::try_from_iter(characters)?,
+                    len: length.try_into().ok()?,
+                    blanks: 0,
+                })
+            })
+    }
+
+    /// The characters in this key, with repeats
+    pub fn characters(&self) -> impl Iterator<Item = Character> {
+        self.inner.into_iter()
+    }
+
+    /// The letters in this key in alphabetical order and lower case, followed by a `?` for each blank
+    pub fn letters(&self) -> String {
+        self.characters()
+            .map(|c| c.as_char().to_ascii_lowercase())
+            .sorted()
+            .chain(std::iter::repeat_n('?', self.blanks()))
+            .collect()
     }
 }
 
 impl Ord for AnagramKey {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.inner.cmp(&other.inner)
+        self.inner
+            .cmp(&other.inner)
+            .then(self.blanks.cmp(&other.blanks))
     }
 }
 
@@ -75,19 +169,35 @@ impl Display for AnagramKey {
             for char in self.inner.into_iter() {
                 f.write_char(char.as_char())?;
             }
+            for _ in 0..self.blanks {
+                f.write_char('?')?;
+            }
         }
 
         std::fmt::Result::Ok(())
     }
 }
 
+impl Serialize for AnagramKey {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
 impl FromStr for AnagramKey {
     type Err = anyhow::Error;
 
+    /// Letters are case insensitive and `?` is a blank. Other characters are ignored.
     fn from_str(s: &str) -> Result<Self, anyhow::Error> {
         let mut len: u8 = 0;
 
         let s = s.to_ascii_lowercase();
+        let blanks: u8 = s
+            .chars()
+            .filter(|c| *c == '?')
+            .count()
+            .try_into()
+            .map_err(|_| anyhow!("Too many blanks"))?;
 
         let chars = s
             .chars()
@@ -97,7 +207,11 @@ impl FromStr for AnagramKey {
 
         let inner = PrimeBag128::try_from_iter(chars).ok_or(anyhow!("String is too long"))?;
 
-        Ok(AnagramKey { inner, len })
+        let len = len
+            .checked_add(blanks)
+            .ok_or(anyhow!("String is too long"))?;
+
+        Ok(AnagramKey { inner, len, blanks })
     }
 }
 
@@ -136,4 +250,42 @@ mod tests {
 
         assert_eq!(clint, subbed);
     }
+
+    #[test]
+    fn test_sub_with_blanks() {
+        let rack = AnagramKey::from_str("retain??").unwrap();
+        let word = AnagramKey::from_str("retains").unwrap();
+
+        let subbed = rack.try_sub(word).unwrap();
+
+        assert_eq!(subbed.blanks(), 1);
+        assert_eq!(subbed.len(), 1);
+        assert_eq!(subbed.letters(), "?");
+    }
+
+    #[test]
+    fn test_sub_with_too_few_blanks() {
+        let rack = AnagramKey::from_str("cat?").unwrap();
+        let word = AnagramKey::from_str("dog").unwrap();
+
+        assert_eq!(rack.try_sub(word), None);
+    }
+
+    #[test]
+    fn test_sub_keys_of_length() {
+        let key = AnagramKey::from_str("seen").unwrap();
+        let letters = key
+            .sub_keys_of_length(2)
+            .map(|k| k.letters())
+            .collect::<Vec<_>>();
+
+        assert_eq!(letters, ["ee", "en", "es", "ns"]);
+        assert_eq!(key.sub_keys_of_length(5).count(), 0);
+    }
+
+    #[test]
+    fn test_all_of_length_past_capacity() {
+        assert_eq!(AnagramKey::all_of_length(2).count(), 351);
+        assert_eq!(AnagramKey::all_of_length(40).count(), 0);
+    }
 }
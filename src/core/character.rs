@@ -68,6 +68,11 @@ impl std::fmt::Display for Character {
 }
 
 impl Character {
+    /// The position of this letter in the alphabet, from A = 1 to Z = 26
+    pub fn ordinal(&self) -> u32 {
+        (self.as_char() as u32) - ('A' as u32) + 1
+    }
+
     pub fn as_char(&self) -> char {
         match self {
             Character::A => 'A',
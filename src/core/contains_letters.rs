@@ -0,0 +1,41 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::core::prelude::*;
+
+/// Words whose letters include all of the required letters.
+/// If `extras` is set, any other letters in the word must belong to it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ContainsLetters {
+    pub required: AnagramKey,
+    /// Always a `CharacterClass::Set`. An empty set means the word has exactly the required letters.
+    pub extras: Option<CharacterClass>,
+}
+
+impl ContainsLetters {
+    pub fn allow(&self, term: &Homograph) -> bool {
+        let Some(remainder) = AnagramKey::from_str(&term.text)
+            .ok()
+            .and_then(|key| key.try_sub(self.required))
+        else {
+            return false;
+        };
+
+        match &self.extras {
+            None => true,
+            Some(extras) => remainder
+                .characters()
+                .all(|c| extras.allow(c.as_char(), &[])),
+        }
+    }
+}
+
+impl Display for ContainsLetters {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "~{}", self.required.letters())?;
+        if let Some(extras) = &self.extras {
+            let extras = extras.to_string();
+            write!(f, "{}", extras.trim_start_matches('@'))?;
+        }
+        Ok(())
+    }
+}
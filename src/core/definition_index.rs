@@ -0,0 +1,218 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    sync::Arc,
+};
+
+use itertools::Itertools;
+
+use crate::core::prelude::*;
+
+/// Words which are too common in definitions to be worth searching for
+const STOP_WORDS: [&str; 20] = [
+    "a", "an", "and", "are", "as", "at", "by", "for", "from", "in", "into", "is", "it", "of", "on",
+    "or", "that", "the", "to", "with",
+];
+
+/// The words of every definition, pointing back to the homographs they define
+pub struct DefinitionIndex {
+    /// The indexes of the homographs with each token in their definitions, in order
+    postings: HashMap<String, Vec<usize>>,
+    homographs: usize,
+}
+
+impl From<&TermDict> for DefinitionIndex {
+    fn from(term_dict: &TermDict) -> Self {
+        let mut postings: HashMap<String, Vec<usize>> = HashMap::new();
+
+        for (index, homograph) in term_dict.homographs.iter().enumerate() {
+            for token in Self::homograph_tokens(homograph) {
+                postings.entry(token).or_default().push(index);
+            }
+        }
+
+        Self {
+            postings,
+            homographs: term_dict.homographs.len(),
+        }
+    }
+}
+
+impl DefinitionIndex {
+    /// The lower case words of some text, without stop words and with a plural s removed
+    pub fn tokens(text: &str) -> impl Iterator<Item = String> + '_ {
+        text.split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(|word| word.to_ascii_lowercase())
+            .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+            .map(|mut word| {
+                if word.len() > 3 && word.ends_with('s') && !word.ends_with("ss") {
+                    word.pop();
+                }
+                word
+            })
+    }
+
+    /// The distinct tokens of all the definitions of a homograph
+    pub fn homograph_tokens(homograph: &Homograph) -> HashSet<String> {
+        homograph
+            .meanings
+            .iter()
+            .filter_map(|m| m.definition)
+            .flat_map(Self::tokens)
+            .collect()
+    }
+
+    /// How much a token says about a definition: rarer tokens say more
+    fn weight(&self, token: &str) -> f64 {
+        let count = self
+            .postings
+            .get(token)
+            .map(|p| p.len())
+            .unwrap_or_default();
+        (self.homographs as f64 / count.max(1) as f64).ln()
+    }
+
+    /// The indexes of the homographs matching every clause, most relevant first.
+    /// Relevance is the total weight of the tokens found.
+    pub fn search(&self, clauses: &[Vec<String>]) -> Vec<usize> {
+        let mut scores: HashMap<usize, (usize, f64)> = HashMap::new();
+
+        for clause in clauses {
+            let mut clause_scores: HashMap<usize, f64> = HashMap::new();
+
+            for token in clause.iter().unique() {
+                let weight = self.weight(token);
+                for index in self.postings.get(token).into_iter().flatten() {
+                    *clause_scores.entry(*index).or_default() += weight;
+                }
+            }
+
+            for (index, score) in clause_scores {
+                let entry = scores.entry(index).or_default();
+                entry.0 += 1;
+                entry.1 += score;
+            }
+        }
+
+        scores
+            .into_iter()
+            .filter(|(_, (matched, _))| *matched == clauses.len())
+            .sorted_by(|(a, (_, a_score)), (b, (_, b_score))| {
+                b_score.total_cmp(a_score).then(a.cmp(b))
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+/// Words whose definitions match some clauses.
+/// Every clause must match, and a clause matches if the definition contains any of its tokens.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DefinitionSearch {
+    pub clauses: Vec<Vec<String>>,
+    /// The indexes of the matching homographs, most relevant first, once looked up in the index
+    pub matches: Option<Arc<[usize]>>,
+    /// The text of the matching homographs, once looked up in the index
+    pub matched_text: Option<Arc<HashSet<String>>>,
+}
+
+impl DefinitionSearch {
+    pub fn new(clauses: Vec<Vec<String>>) -> Self {
+        Self {
+            clauses,
+            matches: None,
+            matched_text: None,
+        }
+    }
+
+    /// Look up the matching words in the definition index
+    pub fn upgrade(&mut self, dict: &WordContext) {
+        let matches = dict.definition_index.search(&self.clauses);
+        let matched_text = matches
+            .iter()
+            .map(|i| dict.term_dict.homographs[*i].text.to_string())
+            .collect();
+
+        self.matches = Some(matches.into());
+        self.matched_text = Some(Arc::new(matched_text));
+    }
+
+    /// Words matched in the index if the search has been upgraded, otherwise words whose definitions match
+    pub fn allow(&self, term: &Homograph) -> bool {
+        if let Some(matched_text) = &self.matched_text {
+            return matched_text.contains(term.text.as_ref());
+        }
+
+        let tokens = DefinitionIndex::homograph_tokens(term);
+        self.clauses
+            .iter()
+            .all(|clause| clause.iter().any(|t| tokens.contains(t)))
+    }
+}
+
+impl Display for DefinitionSearch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Ok(clause) = self.clauses.iter().exactly_one() {
+            return write!(f, "\"{}\"", clause.join(" "));
+        }
+
+        let clauses = self
+            .clauses
+            .iter()
+            .map(|clause| match clause.as_slice() {
+                [token] => token.clone(),
+                _ => format!("\"{}\"", clause.join(" ")),
+            })
+            .join(" & ");
+
+        write!(f, "def:({clauses})")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::core::prelude::*;
+    use crate::language::prelude::*;
+    use ntest::test_case;
+
+    const WORDS: &str = "n\tmouse\tsmall rodent with a long tail\t\nn\trat\tlong-tailed rodent, larger than a mouse\t\nn\tParis\tthe capital city of France\t\nn\tlondon\tthe capital and largest city of England\t\nn\tRome\tthe capital of Italy\t\nn\tcity\ta large town\t\nn\tcapital\twealth in the form of money\t\nn\tcat\tsmall furry animal with whiskers\t\n";
+
+    #[test_case("The small mice", "small mice", name = "stop_words")]
+    #[test_case("rodents, cats and glass", "rodent cat glass", name = "plurals")]
+    #[test_case("long-tailed", "long tailed", name = "hyphen")]
+    fn test_tokens(text: &str, expected: &str) {
+        let actual = DefinitionIndex::tokens(text).join(" ");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test_case("\"small rodent\"", "mouse rat cat", name = "ranked")]
+    #[test_case("def:(rodent & small)", "mouse", name = "all_clauses")]
+    #[test_case("def:\"capital city\"", "Paris london Rome", name = "any_word")]
+    #[test_case("6 + def:\"capital city\"", "london", name = "with_length")]
+    #[test_case("?o* + \"capital city\"", "london Rome", name = "with_pattern")]
+    #[test_case("def:(capital & city)", "Paris london", name = "capital_city")]
+    #[test_case("def:elephant", "", name = "no_matches")]
+    fn test_definition_search(input: &str, expected: &str) {
+        let dict = WordContext::from(TermDict::from_csv(WORDS).unwrap());
+        let mut question = question_parse(input).unwrap();
+        question.upgrade_literals(&dict);
+
+        let actual = question.solve(&dict).map(|s| s.get_text()).join(" ");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_definition_search_without_upgrade() {
+        let dict = WordContext::from(TermDict::from_csv(WORDS).unwrap());
+        let question = question_parse("def:(capital & city)").unwrap();
+
+        let actual = question.solve(&dict).map(|s| s.get_text()).join(" ");
+
+        assert_eq!(actual, "Paris london");
+    }
+}
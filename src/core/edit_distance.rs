@@ -0,0 +1,44 @@
+use itertools::Itertools;
+
+/// The number of insertions, deletions, substitutions and transpositions needed to turn one string into the other
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect_vec();
+    let b = b.chars().collect_vec();
+
+    let mut rows = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::prelude::*;
+    use ntest::test_case;
+
+    #[test_case("cat", "cat", 0, name = "same")]
+    #[test_case("cat", "cart", 1, name = "insertion")]
+    #[test_case("noun", "nuon", 1, name = "transposition")]
+    #[test_case("", "dog", 3, name = "empty")]
+    fn test_edit_distance(a: &str, b: &str, expected: usize) {
+        assert_eq!(edit_distance(a, b), expected);
+    }
+}
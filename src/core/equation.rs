@@ -3,7 +3,7 @@ use itertools::Itertools;
 
 use smallvec::SmallVec;
 
-use std::{rc::Rc, str::FromStr};
+use std::{fmt::Display, rc::Rc, str::FromStr};
 
 use crate::core::prelude::*;
 
@@ -14,10 +14,128 @@ pub struct Equation {
     pub right: Expression,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum EqualityOperator {
     Anagram,
     Spoonerism,
+    /// The right side uses some of the letters of the left side
+    SubAnagram,
+    /// The right side spells the left side backwards
+    Reversal,
+    /// The right side is hidden in the letters of the left side
+    Hidden,
+    /// The right side is hidden backwards in the letters of the left side
+    HiddenReversed,
+    /// The right side is an anagram of the left side with some letters added
+    AddLetters(usize),
+    /// The right side is an anagram of the left side with some letters removed
+    RemoveLetters(usize),
+    /// The right side is an anagram of the left side with some letters changed
+    ChangeLetters(usize),
+    /// The letters of the right side add up to the same value as the letters of the left side in the cipher
+    Gematria(Cipher),
+    /// The right side is the left side with some words replaced by one which sounds similar
+    Pun,
+}
+
+/// Creates an equality operator which edits the given number of letters
+type EditOperator = fn(usize) -> EqualityOperator;
+
+impl EqualityOperator {
+    /// The symbols of the equality operators which take no argument
+    pub const SYMBOLS: [(&'static str, EqualityOperator); 8] = [
+        ("=a", EqualityOperator::Anagram),
+        ("=s", EqualityOperator::Spoonerism),
+        ("=b", EqualityOperator::SubAnagram),
+        ("=r", EqualityOperator::Reversal),
+        ("=h", EqualityOperator::Hidden),
+        ("=hr", EqualityOperator::HiddenReversed),
+        ("=g", EqualityOperator::Gematria(Cipher::Simple)),
+        ("=p", EqualityOperator::Pun),
+    ];
+
+    /// The symbols of the equality operators which are followed by a number of letters
+    pub const EDIT_SYMBOLS: [(&'static str, EditOperator); 3] = [
+        ("=+", EqualityOperator::AddLetters),
+        ("=-", EqualityOperator::RemoveLetters),
+        ("=~", EqualityOperator::ChangeLetters),
+    ];
+
+    /// The most letters which can be added, removed or changed
+    pub const MAX_EDIT_LETTERS: usize = 3;
+
+    /// An example of each equality operator, as it would be written in a query
+    pub fn examples() -> impl Iterator<Item = String> {
+        Self::SYMBOLS
+            .iter()
+            .map(|(symbol, _)| symbol.to_string())
+            .chain(
+                Self::EDIT_SYMBOLS
+                    .iter()
+                    .map(|(symbol, _)| format!("{symbol}1")),
+            )
+    }
+}
+
+impl Display for EqualityOperator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EqualityOperator::AddLetters(n)
+            | EqualityOperator::RemoveLetters(n)
+            | EqualityOperator::ChangeLetters(n) => {
+                let (symbol, _) = Self::EDIT_SYMBOLS
+                    .iter()
+                    .find(|(_, edit)| edit(*n) == *self)
+                    .expect("Every edit has a symbol");
+                write!(f, "{symbol}{n}")
+            }
+            EqualityOperator::Gematria(cipher @ Cipher::Custom(_)) => write!(f, "=g{cipher}"),
+            EqualityOperator::Gematria(cipher) if *cipher != Cipher::Simple => {
+                write!(f, "=g({cipher})")
+            }
+            _ => {
+                let (symbol, _) = Self::SYMBOLS
+                    .iter()
+                    .find(|(_, operator)| operator == self)
+                    .expect("Every equality operator has a symbol");
+                write!(f, "{symbol}")
+            }
+        }
+    }
+}
+
+impl FromStr for EqualityOperator {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((_, operator)) = Self::SYMBOLS
+            .iter()
+            .find(|(symbol, _)| symbol.eq_ignore_ascii_case(s))
+        {
+            return Ok(operator.clone());
+        }
+
+        let Some((edit, n)) = Self::EDIT_SYMBOLS
+            .iter()
+            .find_map(|(symbol, edit)| s.strip_prefix(symbol).map(|n| (edit, n)))
+        else {
+            anyhow::bail!("Could not parse {} as equality operator", s)
+        };
+
+        match usize::from_str(n)? {
+            0 => anyhow::bail!("Must edit at least one letter"),
+            n if n > Self::MAX_EDIT_LETTERS => {
+                anyhow::bail!("Can edit at most {} letters", Self::MAX_EDIT_LETTERS)
+            }
+            n => Ok(edit(n)),
+        }
+    }
+}
+
+impl Display for Equation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.left, self.operator, self.right)
+    }
 }
 
 impl Equation {
@@ -39,54 +157,106 @@ impl Equation {
                 true
             }
             EqualityOperator::Spoonerism => false,
+            EqualityOperator::SubAnagram | EqualityOperator::Gematria(_) => {
+                let max_right_words = if self.operator == EqualityOperator::SubAnagram {
+                    Self::MAX_SUB_ANAGRAM_WORDS
+                } else {
+                    Self::MAX_GEMATRIA_WORDS
+                };
+                let right_words = self.right.to_anagram_settings().max_words;
+                if right_words.is_none_or(|max| max > max_right_words) {
+                    return true;
+                }
+
+                let left_options = self.left.count_options(dict).unwrap_or(usize::MAX);
+                left_options > Self::EASY_OPTIONS
+            }
+            EqualityOperator::AddLetters(n)
+            | EqualityOperator::RemoveLetters(n)
+            | EqualityOperator::ChangeLetters(n)
+                if n > EqualityOperator::MAX_EDIT_LETTERS =>
+            {
+                true
+            }
+            EqualityOperator::Hidden
+            | EqualityOperator::HiddenReversed
+            | EqualityOperator::AddLetters(_)
+            | EqualityOperator::RemoveLetters(_)
+            | EqualityOperator::ChangeLetters(_)
+            | EqualityOperator::Pun => {
+                let left_options = self.left.count_options(dict).unwrap_or(usize::MAX);
+                left_options > Self::EASY_OPTIONS
+            }
+            EqualityOperator::Reversal => {
+                let left_options = self.left.count_options(dict).unwrap_or(usize::MAX);
+                let right_options = self.right.count_options(dict).unwrap_or(usize::MAX);
+                left_options.min(right_options) > Self::EASY_OPTIONS
+            }
         }
     }
 
-    #[auto_enum(Iterator)]
+    /// Split a word into its onset and the rest.
+    /// The onset is the leading consonants, counting the u of qu and treating y as a vowel after the first letter.
+    pub fn split_onset(text: &str) -> (&str, &str) {
+        let mut previous = None;
+        let end = text
+            .char_indices()
+            .find(|(i, c)| {
+                let c = c.to_ascii_lowercase();
+                let is_consonant = match c {
+                    'a' | 'e' | 'i' | 'o' => false,
+                    'u' => previous == Some('q'),
+                    'y' => *i == 0,
+                    _ => c.is_ascii_alphabetic(),
+                };
+                previous = Some(c);
+                !is_consonant
+            })
+            .map(|(i, _)| i)
+            .unwrap_or(text.len());
+
+        text.split_at(end)
+    }
+
+    /// Rotate the onsets of the words of each left solution, trying every rotation
     fn solve_spoonerism<'a>(
         left_expression: &'a Expression,
         right_expression: &'a Expression,
         dict: &'a WordContext,
     ) -> impl Iterator<Item = SpoonerismSolution> + 'a {
-        if !left_expression.allow_number_of_words(2) || !right_expression.allow_number_of_words(2) {
-            return std::iter::empty::<SpoonerismSolution>();
-        }
-
-        let result = left_expression
-            .solve(dict)
-            .filter(|x| x.homographs.len() == 2)
-            .filter_map(|left| {
-                let w1 = left.homographs[0].clone();
-                let w2 = left.homographs[1].clone();
-                let nw1 = w2.text[0..1].to_string() + &w1.text[1..];
-
-                let h1 = dict.term_dict.try_find(nw1.as_str());
-                h1.as_ref()?;
-
-                let nw2 = w1.text[0..1].to_string() + &w2.text[1..];
-
-                let h2 = dict.term_dict.try_find(nw2.as_str());
-                h2.as_ref()?;
-
-                let right_homographs = smallvec::smallvec![h1.unwrap(), h2.unwrap()];
-
-                let right = ExpressionSolution {
-                    homographs: right_homographs,
-                };
-
-                if !right_expression.allow(&right) {
-                    return None;
-                }
-                let solution = SpoonerismSolution { left, right };
-
-                if solution.is_trivial() {
-                    return None;
-                }
-
-                Some(solution)
-            });
+        left_expression
+            .solve_as_letters(dict)
+            .filter(|x| x.homographs.len() >= 2)
+            .flat_map(move |left| {
+                let parts = left
+                    .homographs
+                    .iter()
+                    .map(|h| Self::split_onset(&h.text))
+                    .collect_vec();
+
+                (1..parts.len())
+                    .filter_map(|shift| {
+                        let homographs = (0..parts.len())
+                            .map(|i| {
+                                let (onset, _) = parts[(i + shift) % parts.len()];
+                                let (_, rest) = parts[i];
+                                dict.term_dict.try_find(&(onset.to_string() + rest))
+                            })
+                            .collect::<Option<SmallVec<_>>>()?;
+
+                        let right = ExpressionSolution { homographs };
+                        if !right_expression.allow(&right) {
+                            return None;
+                        }
 
-        return result;
+                        let solution = SpoonerismSolution {
+                            left: left.clone(),
+                            right,
+                        };
+                        (!solution.is_trivial()).then_some(solution)
+                    })
+                    .collect_vec()
+            })
     }
 
     #[auto_enum(Iterator)]
@@ -97,9 +267,12 @@ impl Equation {
         extracted_literals: Rc<SmallVec<[(Homograph, usize); 2]>>,
         dict: &WordContext,
     ) -> impl Iterator<Item = AnagramSolution> + '_ {
-        if let Some(key) = AnagramKey::from_str(left.get_text().as_str())
+        if let Some((blanks, key)) = AnagramKey::from_str(left.get_text().as_str())
             .ok()
-            .and_then(|k| k.try_sub(key_to_subtract))
+            .and_then(|k| {
+                let blanks = k.blanks() + key_to_subtract.blanks();
+                k.try_sub(key_to_subtract).map(|key| (blanks, key))
+            })
         {
             let settings = dehydrated_right.to_anagram_settings();
             let lefts = dict
@@ -107,9 +280,12 @@ impl Equation {
                 .solve(key, settings)
                 .map(move |r| (left.clone(), r))
                 .filter_map(move |(left, s)| dehydrated_right.order_to_allow(s).map(|r| (left, r)))
-                .map(move |(left, extra_rights)| AnagramSolution {
-                    left,
-                    right: Equation::hydrate(extra_rights, &extracted_literals),
+                .map(move |(left, extra_rights)| {
+                    AnagramSolution::new(
+                        left,
+                        Equation::hydrate(extra_rights, &extracted_literals),
+                        blanks,
+                    )
                 })
                 .filter(|x| !x.is_trivial());
 
@@ -132,7 +308,7 @@ impl Equation {
                 let rc_dr = Rc::from(dehydrated_right);
                 let rc_ex_l = Rc::from(extracted_literals);
 
-                return left.solve(dict).flat_map(move |left| {
+                return left.solve_as_letters(dict).flat_map(move |left| {
                     Self::solve_anagram_dehydrated(
                         left,
                         key_to_subtract,
@@ -161,7 +337,7 @@ impl Equation {
                     .collect_vec()
                     .into();
 
-                return left.solve(dict).flat_map(move |left| {
+                return left.solve_as_letters(dict).flat_map(move |left| {
                     let results = dehydrated_rights
                         .clone()
                         .iter()
@@ -174,11 +350,7 @@ impl Equation {
                                     Rc::clone(extracted_literals),
                                     dict,
                                 )
-                                .filter(|solution| {
-                                    right_as_many.terms.iter().all(|t| {
-                                        solution.right.homographs.iter().any(|h| t.allow(h))
-                                    })
-                                })
+                                .filter(|solution| right_as_many.allow_terms(&solution.right))
                             },
                         )
                         .collect_vec();
@@ -191,15 +363,14 @@ impl Equation {
         let settings = right.to_anagram_settings();
 
         let s = left
-            .solve(dict)
+            .solve_as_letters(dict)
             .flat_map(move |left| {
-                dict.anagram_dict
-                    .solve_for_word(&left.get_text(), settings)
+                let key = AnagramKey::from_str(left.get_text().as_str()).ok();
+                let blanks = key.map_or(0, |k| k.blanks());
+                key.into_iter()
+                    .flat_map(move |key| dict.anagram_dict.solve(key, settings))
                     .filter_map(|s| right.order_to_allow(s))
-                    .map(move |right| AnagramSolution {
-                        left: left.clone(),
-                        right,
-                    })
+                    .map(move |right| AnagramSolution::new(left.clone(), right, blanks))
             })
             .filter(|x| !x.is_trivial());
         return s;
@@ -231,8 +402,11 @@ impl Equation {
             return std::iter::empty();
         }
 
-        let left_first = {
-            match left_options.cmp(&right_options) {
+        //Blanks can only be used on the side which is solved first
+        let left_first = match (self.left.has_blanks(), self.right.has_blanks()) {
+            (true, false) => true,
+            (false, true) => false,
+            _ => match left_options.cmp(&right_options) {
                 std::cmp::Ordering::Less => true,
                 std::cmp::Ordering::Equal => match (&self.left, &self.right) {
                     (Expression::Many(l), Expression::Many(r)) => {
@@ -250,7 +424,7 @@ impl Equation {
                     }
                 },
                 std::cmp::Ordering::Greater => false,
-            }
+            },
         };
 
         if left_first {
@@ -260,6 +434,269 @@ impl Equation {
         }
     }
 
+    /// The most words the right side of a sub-anagram can have, as all of its solutions are sorted
+    const MAX_SUB_ANAGRAM_WORDS: usize = 3;
+
+    /// For each solution of the left side, find the solutions of the right side made from its letters.
+    /// These are ordered with the most letters used first.
+    fn solve_as_sub_anagram<'a>(
+        &'a self,
+        dict: &'a WordContext,
+    ) -> impl Iterator<Item = SubAnagramSolution> + 'a {
+        let mut settings = self.right.to_anagram_settings();
+        settings.max_words = Some(
+            settings
+                .max_words
+                .map_or(Self::MAX_SUB_ANAGRAM_WORDS, |max| {
+                    max.min(Self::MAX_SUB_ANAGRAM_WORDS)
+                }),
+        );
+
+        self.left.solve_as_letters(dict).flat_map(move |left| {
+            let Ok(key) = AnagramKey::from_str(left.get_text().as_str()) else {
+                return vec![];
+            };
+
+            dict.anagram_dict
+                .solve_partial(key, settings)
+                .filter_map(|(right, remainder)| {
+                    self.right
+                        .order_to_allow(right)
+                        .map(|right| (right, remainder))
+                })
+                .map(|(right, remainder)| SubAnagramSolution {
+                    left: left.clone(),
+                    right,
+                    remainder,
+                })
+                .filter(|x| !x.is_trivial())
+                .sorted_by(|a, b| {
+                    b.letters_used()
+                        .cmp(&a.letters_used())
+                        .then_with(|| a.cmp(b))
+                })
+                .collect_vec()
+        })
+    }
+
+    /// Solve whichever side has fewer options, then look up the reversal of each solution
+    #[auto_enum(Iterator)]
+    fn solve_as_reversal<'a>(
+        &'a self,
+        dict: &'a WordContext,
+    ) -> impl Iterator<Item = ReversalSolution> + 'a {
+        let left_options = self.left.count_options(dict).unwrap_or(usize::MAX);
+        let right_options = self.right.count_options(dict).unwrap_or(usize::MAX);
+
+        if left_options <= right_options {
+            Equation::solve_reversal(&self.left, &self.right, dict)
+        } else {
+            Equation::solve_reversal(&self.right, &self.left, dict).map(|x| x.flip())
+        }
+    }
+
+    fn solve_reversal<'a>(
+        left: &'a Expression,
+        right: &'a Expression,
+        dict: &'a WordContext,
+    ) -> impl Iterator<Item = ReversalSolution> + 'a {
+        let settings = right.to_anagram_settings();
+
+        left.solve_as_letters(dict)
+            .flat_map(move |left| {
+                dict.reversal_dict
+                    .solve_for_word(&left.get_text(), settings)
+                    .filter(|s| right.allow(s))
+                    .map(move |right| ReversalSolution {
+                        left: left.clone(),
+                        right,
+                    })
+                    .collect_vec()
+            })
+            .filter(|x| !x.is_trivial())
+    }
+
+    /// Look for the right side in every run of letters of each solution of the left side
+    fn solve_as_hidden<'a>(
+        &'a self,
+        dict: &'a WordContext,
+        reversed: bool,
+    ) -> impl Iterator<Item = HiddenSolution> + 'a {
+        let settings = self.right.to_anagram_settings();
+
+        self.left.solve_as_letters(dict).flat_map(move |left| {
+            let letters = ReversalDict::letters(&left.get_text()).collect::<String>();
+
+            (0..letters.len())
+                .flat_map(|start| (start + 1..=letters.len()).map(move |end| start..end))
+                .flat_map(|range| {
+                    let run = &letters[range];
+                    if reversed {
+                        dict.reversal_dict
+                            .solve_for_word(run, settings)
+                            .collect_vec()
+                    } else {
+                        dict.reversal_dict
+                            .solve_for_text(run, settings)
+                            .collect_vec()
+                    }
+                })
+                .filter(|right| self.right.allow(right))
+                .map(|right| HiddenSolution {
+                    left: left.clone(),
+                    right,
+                    reversed,
+                })
+                .filter(|x| !x.is_trivial())
+                .unique_by(|x| x.right.get_text())
+                .collect_vec()
+        })
+    }
+
+    /// For each solution of the left side, remove and then add every combination of letters and find the anagrams.
+    /// The letters removed and added must be different.
+    fn solve_as_edit<'a>(
+        &'a self,
+        dict: &'a WordContext,
+        removed: usize,
+        added: usize,
+    ) -> impl Iterator<Item = EditSolution> + 'a {
+        let settings = self.right.to_anagram_settings();
+
+        self.left.solve_as_letters(dict).flat_map(move |left| {
+            let Ok(key) = AnagramKey::from_str(left.get_text().as_str()) else {
+                return vec![];
+            };
+
+            key.sub_keys_of_length(removed)
+                .filter_map(|r| key.try_sub(r).map(|remaining| (r, remaining)))
+                .flat_map(|(r, remaining)| {
+                    AnagramKey::all_of_length(added)
+                        .filter(move |a| !a.characters().any(|c| r.characters().contains(&c)))
+                        .filter_map(move |a| remaining.try_add(a).map(|new_key| (r, a, new_key)))
+                })
+                .flat_map(|(r, a, new_key)| {
+                    let left = left.clone();
+                    dict.anagram_dict
+                        .solve(new_key, settings)
+                        .filter_map(|s| self.right.order_to_allow(s))
+                        .map(move |right| EditSolution {
+                            left: left.clone(),
+                            right,
+                            added: a,
+                            removed: r,
+                        })
+                })
+                .collect_vec()
+        })
+    }
+
+    /// The most words the right side of a gematria equation can have, as every combination of their values is tried
+    const MAX_GEMATRIA_WORDS: usize = 3;
+
+    /// For each solution of the left side, find solutions of the right side with the same gematria value in the cipher
+    #[auto_enum(Iterator)]
+    fn solve_as_gematria<'a>(
+        &'a self,
+        cipher: &'a Cipher,
+        dict: &'a WordContext,
+    ) -> impl Iterator<Item = GematriaSolution> + 'a {
+        match &self.right {
+            Expression::FixedLength(right) => {
+                //The candidates for each word, sorted by value
+                let candidates: Rc<[_]> = right
+                    .words
+                    .iter()
+                    .map(|w| {
+                        w.solve(&dict.term_dict)
+                            .map(|h| (cipher.value(&h.text), h))
+                            .sorted_by_key(|(v, _)| *v)
+                            .collect_vec()
+                    })
+                    .collect();
+
+                self.left.solve_as_letters(dict).flat_map(move |left| {
+                    let value = cipher.solution_value(&left);
+
+                    GematriaIterator::create(candidates.clone(), value)
+                        .map(move |right| GematriaSolution {
+                            left: left.clone(),
+                            right,
+                            value,
+                        })
+                        .filter(|x| !x.is_trivial())
+                })
+            }
+            Expression::Many(right) => self.left.solve_as_letters(dict).flat_map(move |left| {
+                let value = cipher.solution_value(&left);
+
+                right
+                    .solve(dict)
+                    .filter(move |r| cipher.solution_value(r) == value)
+                    .map(move |right| GematriaSolution {
+                        left: left.clone(),
+                        right,
+                        value,
+                    })
+                    .filter(|x| !x.is_trivial())
+            }),
+        }
+    }
+
+    /// The most words of the left side which can be replaced by a single word
+    const MAX_PUN_WORDS: usize = 2;
+
+    /// For each solution of the left side, replace a run of words with a single word from the right side which sounds similar.
+    /// The closest sounding replacements come first.
+    fn solve_as_pun<'a>(&'a self, dict: &'a WordContext) -> impl Iterator<Item = PunSolution> + 'a {
+        let candidates: SoundsLikeIndex = self
+            .right
+            .solve(dict)
+            .filter(|s| s.homographs.len() == 1)
+            .map(|s| s.homographs[0].clone())
+            .collect();
+
+        self.left.solve_as_letters(dict).flat_map(move |left| {
+            let words = left.homographs.len();
+
+            (0..words)
+                .flat_map(|start| {
+                    (start + 1..=(start + Self::MAX_PUN_WORDS).min(words))
+                        .map(move |end| start..end)
+                })
+                .flat_map(|range| {
+                    let replaced = &left.homographs[range.clone()];
+                    let keys = SoundKeys::for_homographs(replaced);
+                    let text = replaced.iter().map(|h| h.text.clone()).join(" ");
+                    let left = left.clone();
+
+                    candidates
+                        .sounds_like(&keys)
+                        .into_iter()
+                        .filter(move |(_, h)| !h.text.eq_ignore_ascii_case(&text))
+                        .map(move |(distance, replacement)| {
+                            let right = ExpressionSolution {
+                                homographs: left.homographs[..range.start]
+                                    .iter()
+                                    .chain(std::iter::once(replacement))
+                                    .chain(&left.homographs[range.end..])
+                                    .cloned()
+                                    .collect(),
+                            };
+                            let solution = PunSolution {
+                                left: left.clone(),
+                                right,
+                                replaced: range.clone(),
+                            };
+                            (distance, solution)
+                        })
+                })
+                .sorted_by_key(|(distance, _)| *distance)
+                .map(|(_, solution)| solution)
+                .collect_vec()
+        })
+    }
+
     fn solve_as_spoonerism<'a>(
         &'a self,
         dict: &'a WordContext,
@@ -272,11 +709,49 @@ impl Equation {
         &'a self,
         dict: &'a WordContext,
     ) -> impl Iterator<Item = QuestionSolution> + 'a {
-        match self.operator {
+        match &self.operator {
             EqualityOperator::Anagram => self.solve_as_anagram(dict).map(QuestionSolution::Anagram),
             EqualityOperator::Spoonerism => self
                 .solve_as_spoonerism(dict)
                 .map(QuestionSolution::Spoonerism),
+            EqualityOperator::SubAnagram => self
+                .solve_as_sub_anagram(dict)
+                .map(QuestionSolution::SubAnagram),
+            EqualityOperator::Reversal => {
+                self.solve_as_reversal(dict).map(QuestionSolution::Reversal)
+            }
+            EqualityOperator::Hidden => self
+                .solve_as_hidden(dict, false)
+                .map(QuestionSolution::Hidden),
+            EqualityOperator::HiddenReversed => self
+                .solve_as_hidden(dict, true)
+                .map(QuestionSolution::Hidden),
+            EqualityOperator::AddLetters(n) => {
+                self.solve_as_edit(dict, 0, *n).map(QuestionSolution::Edit)
+            }
+            EqualityOperator::RemoveLetters(n) => {
+                self.solve_as_edit(dict, *n, 0).map(QuestionSolution::Edit)
+            }
+            EqualityOperator::ChangeLetters(n) => {
+                self.solve_as_edit(dict, *n, *n).map(QuestionSolution::Edit)
+            }
+            EqualityOperator::Gematria(cipher) => self
+                .solve_as_gematria(cipher, dict)
+                .map(QuestionSolution::Gematria),
+            EqualityOperator::Pun => self.solve_as_pun(dict).map(QuestionSolution::Pun),
+        }
+    }
+
+    /// Let source letter classes on each side refer to the literals on the other side
+    pub fn bind_source_letters(&mut self) {
+        let left_letters = self.left.literal_letters();
+        let right_letters = self.right.literal_letters();
+
+        if !right_letters.is_empty() {
+            self.left.bind_source_letters(&right_letters);
+        }
+        if !left_letters.is_empty() {
+            self.right.bind_source_letters(&left_letters);
         }
     }
 
@@ -285,3 +760,221 @@ impl Equation {
         self.right.upgrade_literals(dict);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::core::prelude::*;
+    use crate::language::prelude::*;
+    use ntest::test_case;
+
+    const WORDS: &str = "n\tact\t\t\nn\tcat\t\t\nn\tire\t\t\nn\ttic\t\t\nv\tice\t\t\nn\trice\t\t\n";
+
+    #[test_case(
+        "i react =b *",
+        "i react : rice + at; i react : act + eir; i react : cat + eir",
+        name = "longest_first"
+    )]
+    #[test_case("i react =b #v", "i react : ice + art", name = "filtered")]
+    #[test_case(
+        "i react =b * *",
+        "i react : act ire; i react : cat ire",
+        name = "two_words"
+    )]
+    #[test_case("cat =b *", "cat : act", name = "not_trivial")]
+    #[test_case("dog =b *", "", name = "no_solutions")]
+    fn test_sub_anagram(input: &str, expected: &str) {
+        let dict = WordContext::from(TermDict::from_csv(WORDS).unwrap());
+        let question = question_parse(input).unwrap();
+
+        let actual = question
+            .solve(&dict)
+            .take(3)
+            .map(|s| s.get_text())
+            .join("; ");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test_case("i react =b **", true, name = "unlimited_right")]
+    #[test_case("i react =b * * * *", true, name = "four_word_right")]
+    #[test_case("i react =b * * *", false, name = "three_word_right")]
+    #[test_case("act =g **", true, name = "gematria_unlimited_right")]
+    #[test_case("act =g * * *", false, name = "gematria_three_word_right")]
+    fn test_too_difficult(input: &str, expected: bool) {
+        let dict = WordContext::from(TermDict::from_csv(WORDS).unwrap());
+        let question = question_parse(input).unwrap();
+
+        assert_eq!(question.is_too_difficult(&dict), expected);
+    }
+
+    #[test_case("[ice?] =a *", "ice? : rice (? = r)", name = "one_blank")]
+    #[test_case("* =a [?ti]", "tic : ?ti (? = c)", name = "blank_on_right")]
+    #[test_case("[??] =a *", "", name = "too_short")]
+    #[test_case("[ri??] =a #n", "ri?? : rice (? = ce)", name = "two_blanks")]
+    #[test_case("[cat] =a *", "cat : act", name = "no_blanks")]
+    fn test_blanks(input: &str, expected: &str) {
+        let dict = WordContext::from(TermDict::from_csv(WORDS).unwrap());
+        let question = question_parse(input).unwrap();
+
+        let actual = question
+            .solve(&dict)
+            .take(3)
+            .map(|s| s.get_text())
+            .join("; ");
+
+        assert_eq!(actual, expected);
+    }
+
+    const REVERSIBLE: &str =
+        "n\tstressed\t\t\nn\tdesserts\t\t\nn\tlevel\t\t\nn\tpots\t\t\nn\ttub\t\t\nn\tbut\t\t\nv\tlive\t\t\nv\tstop\t\t\nj\tevil\t\t\n";
+
+    #[test_case("stressed =r *", "stressed : desserts", name = "reversal_one_word")]
+    #[test_case("#v =r *", "live : evil; stop : pots", name = "all_verbs")]
+    #[test_case("* =r #j", "live : evil", name = "right_filtered")]
+    #[test_case("level =r *", "", name = "palindrome_is_trivial")]
+    #[test_case("stop tub =r * *", "stop tub : but pots", name = "reversal_two_words")]
+    fn test_reversal(input: &str, expected: &str) {
+        let dict = WordContext::from(TermDict::from_csv(REVERSIBLE).unwrap());
+        let question = question_parse(input).unwrap();
+
+        let actual = question.solve(&dict).map(|s| s.get_text()).join("; ");
+
+        assert_eq!(actual, expected);
+    }
+
+    const HIDDEN: &str = "n\tLondon\t\t\nn\tdonkey\t\t\nn\tcapital\t\t\nn\tcap\t\t\nn\tpot\t\t\nn\ttalon\t\t\nv\ttop\t\t\n";
+
+    #[test_case(
+        "capital on donkey =h 6",
+        "capital on donkey : London",
+        name = "across_words"
+    )]
+    #[test_case(
+        "capital on donkey =h #n",
+        "capital on donkey : cap; capital on donkey : talon; capital on donkey : London",
+        name = "hidden_nouns"
+    )]
+    #[test_case("capital on donkey =h capital", "", name = "not_a_whole_word")]
+    #[test_case("stop =h *", "stop : top", name = "hidden_in_one_word")]
+    #[test_case("stop =h #n", "", name = "not_hidden")]
+    #[test_case("stop =hr *", "stop : pot (reversed)", name = "hidden_reversed")]
+    #[test_case("stop =hr #v", "", name = "hidden_reversed_filtered")]
+    fn test_hidden(input: &str, expected: &str) {
+        let dict = WordContext::from(TermDict::from_csv(HIDDEN).unwrap());
+        let question = question_parse(input).unwrap();
+
+        let actual = question.solve(&dict).map(|s| s.get_text()).join("; ");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test_case("ice =+1 *", "ice : rice (+r)", name = "add_letter")]
+    #[test_case(
+        "rice =-1 *",
+        "rice : ice (-r); rice : ire (-c)",
+        name = "remove_letter"
+    )]
+    #[test_case("cat =~1 *", "cat : tic (-a +i)", name = "change_letter")]
+    #[test_case("ire =+2 #n", "", name = "add_two_letters")]
+    #[test_case("tic =-1 #n", "", name = "remove_letter_too_short")]
+    fn test_edit(input: &str, expected: &str) {
+        let dict = WordContext::from(TermDict::from_csv(WORDS).unwrap());
+        let question = question_parse(input).unwrap();
+
+        let actual = question.solve(&dict).map(|s| s.get_text()).join("; ");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test_case("cat =g 3", "cat : act (24)", name = "same_value")]
+    #[test_case("ice =g * *", "", name = "two_words_too_small")]
+    #[test_case("ire =g *", "ire : tic (32)", name = "gematria_one_word")]
+    #[test_case(
+        "act ice =g * *",
+        "act ice : ice act (41); act ice : ice cat (41); act ice : cat ice (41)",
+        name = "gematria_two_words"
+    )]
+    #[test_case("act =g #v", "", name = "no_verbs")]
+    #[test_case("ire =g(reverse) *", "ire : tic (49)", name = "named_cipher")]
+    #[test_case("cat =g(a=1 c=1 t=1) *", "cat : act (3)", name = "custom_cipher")]
+    fn test_gematria(input: &str, expected: &str) {
+        let dict = WordContext::from(TermDict::from_csv(WORDS).unwrap());
+        let question = question_parse(input).unwrap();
+
+        let actual = question.solve(&dict).map(|s| s.get_text()).join("; ");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test_case("cat =g *", name = "simple_cipher_display")]
+    #[test_case("cat =g(reverse) *", name = "named_cipher_display")]
+    #[test_case("cat =g(a=1 c=2) *", name = "custom_cipher_display")]
+    fn test_gematria_display(input: &str) {
+        assert_eq!(question_parse(input).unwrap().to_string(), input);
+    }
+
+    const PUNS: &str = "n\tlettuce\t\t\tˈlɛtɪs\nn\tprey\t\t\tpreɪ\nn\tknight\t\t\tnaɪt\nn\tphase\t\t\t\nn\tnight\t\t\tnaɪt\nv\tlet\t\t\tlɛt\nv\tpray\t\t\tpreɪ\nv\tfaze\t\t\t\np\tus\t\t\tʌs\n";
+
+    #[test_case(
+        "let us pray =p #n",
+        "let us pray : let us prey (pray → prey); let us pray : lettuce pray (let us → lettuce)",
+        name = "pun_two_words"
+    )]
+    #[test_case(
+        "good night =p #n",
+        "good night : good knight (night → knight)",
+        name = "homophone"
+    )]
+    #[test_case(
+        "phase =p #v",
+        "phase : faze (phase → faze)",
+        name = "spelling_without_pronunciation"
+    )]
+    #[test_case("let us pray =p #p", "", name = "no_similar_words")]
+    fn test_pun(input: &str, expected: &str) {
+        let dict = WordContext::from(TermDict::from_csv(PUNS).unwrap());
+        let mut question = question_parse(input).unwrap();
+        question.upgrade_literals(&dict);
+
+        let actual = question.solve(&dict).map(|s| s.get_text()).join("; ");
+
+        assert_eq!(actual, expected);
+    }
+
+    const SPOONERISMS: &str = "n\tcrushing\t\t\nn\tblow\t\t\nn\tblushing\t\t\nn\tcrow\t\t\nn\tbad\t\t\nn\tcat\t\t\nn\tmat\t\t\nn\tcad\t\t\nn\tbat\t\t\nn\tmad\t\t\nn\tall\t\t\nn\ttin\t\t\nn\ttall\t\t\nn\tin\t\t\nn\tquick\t\t\nn\tsail\t\t\nn\tsick\t\t\nn\tquail\t\t\nn\tbar\t\t\nn\tétude\t\t\n";
+
+    #[test_case(
+        "crushing blow =s * *",
+        "crushing blow : blushing crow",
+        name = "consonant_clusters"
+    )]
+    #[test_case("quick sail =s * *", "quick sail : sick quail", name = "qu_onset")]
+    #[test_case("all tin =s * *", "all tin : tall in", name = "vowel_initial")]
+    #[test_case("all in =s * *", "", name = "no_onsets")]
+    #[test_case(
+        "bad cat mat =s * * *",
+        "bad cat mat : cad mat bat; bad cat mat : mad bat cat",
+        name = "three_words"
+    )]
+    #[test_case("* bar =s * *", "", name = "non_ascii")]
+    fn test_spoonerism(input: &str, expected: &str) {
+        let dict = WordContext::from(TermDict::from_csv(SPOONERISMS).unwrap());
+        let question = question_parse(input).unwrap();
+
+        let actual = question.solve(&dict).map(|s| s.get_text()).join("; ");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test_case("crushing", "cr", "ushing", name = "cluster")]
+    #[test_case("squid", "squ", "id", name = "squ")]
+    #[test_case("rhythm", "rh", "ythm", name = "y_as_vowel")]
+    #[test_case("yellow", "y", "ellow", name = "y_as_consonant")]
+    #[test_case("egg", "", "egg", name = "no_onset")]
+    #[test_case("été", "", "été", name = "non_ascii_vowel")]
+    fn test_split_onset(text: &str, onset: &str, rest: &str) {
+        assert_eq!(Equation::split_onset(text), (onset, rest));
+    }
+}
@@ -1,5 +1,8 @@
+use std::fmt::Display;
+
 use auto_enums::auto_enum;
 use enum_dispatch::enum_dispatch;
+use itertools::Itertools;
 
 use crate::core::prelude::*;
 
@@ -10,6 +13,15 @@ pub enum Expression {
     FixedLength(FixedLengthExpression),
 }
 
+impl Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expression::Many(m) => write!(f, "{m}"),
+            Expression::FixedLength(fl) => write!(f, "{fl}"),
+        }
+    }
+}
+
 impl Expression {
     #[auto_enum(Iterator)]
     pub fn solve<'a>(
@@ -22,6 +34,18 @@ impl Expression {
         }
     }
 
+    /// Like `solve`, but each rack stands for its own letters rather than the words it can form
+    #[auto_enum(Iterator)]
+    pub fn solve_as_letters<'a>(
+        &'a self,
+        dict: &'a WordContext,
+    ) -> impl Iterator<Item = ExpressionSolution> + 'a {
+        match self {
+            Expression::Many(m) => m.solve(dict),
+            Expression::FixedLength(fl) => fl.solve_as_letters(dict),
+        }
+    }
+
     /// Upgrade all literals so they have definitions
     pub fn upgrade_literals(&mut self, dict: &WordContext) {
         match self {
@@ -29,6 +53,41 @@ impl Expression {
             Expression::FixedLength(fl) => fl.upgrade_literals(dict),
         }
     }
+
+    /// The letters of all literal words in this expression, in order
+    pub fn literal_letters(&self) -> String {
+        let literals = match self {
+            Expression::Many(m) => m.terms.iter().filter_map(|t| t.as_literal()).collect_vec(),
+            Expression::FixedLength(fl) => {
+                fl.words.iter().filter_map(|w| w.as_literal()).collect_vec()
+            }
+        };
+
+        literals
+            .into_iter()
+            .flat_map(|h| h.text.chars())
+            .filter(|c| c.is_alphabetic())
+            .map(|c| c.to_ascii_lowercase())
+            .collect()
+    }
+
+    /// Whether any racks in this expression contain blanks
+    pub fn has_blanks(&self) -> bool {
+        let has_blanks = |r: &Rack| r.key.blanks() > 0;
+        match self {
+            Expression::Many(m) => m.terms.iter().filter_map(|t| t.as_rack()).any(has_blanks),
+            Expression::FixedLength(fl) => {
+                fl.words.iter().filter_map(|w| w.as_rack()).any(has_blanks)
+            }
+        }
+    }
+
+    pub fn bind_source_letters(&mut self, letters: &str) {
+        match self {
+            Expression::Many(m) => m.bind_source_letters(letters),
+            Expression::FixedLength(fl) => fl.bind_source_letters(letters),
+        }
+    }
 }
 
 #[enum_dispatch]
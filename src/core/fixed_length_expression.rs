@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{fmt::Display, str::FromStr};
 
 use itertools::Itertools;
 use smallvec::SmallVec;
@@ -21,6 +21,12 @@ impl From<ExpressionSolution> for FixedLengthExpression {
     }
 }
 
+impl Display for FixedLengthExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.words.iter().join(" "))
+    }
+}
+
 impl FixedLengthExpression {
     pub fn solve<'a>(
         &'a self,
@@ -35,6 +41,20 @@ impl FixedLengthExpression {
             })
     }
 
+    /// Like `solve`, but each rack stands for its own letters rather than the words it can form
+    pub fn solve_as_letters<'a>(
+        &'a self,
+        dict: &'a WordContext,
+    ) -> impl Iterator<Item = ExpressionSolution> + 'a {
+        self.words
+            .iter()
+            .map(|w| w.solve_as_letters(&dict.term_dict))
+            .multi_cartesian_product()
+            .map(|homographs| ExpressionSolution {
+                homographs: homographs.into_iter().cloned().collect(),
+            })
+    }
+
     pub fn count_literal_chars(&self) -> usize {
         self.words
             .iter()
@@ -84,6 +104,12 @@ impl FixedLengthExpression {
             w.upgrade_literals(dict)
         }
     }
+
+    pub fn bind_source_letters(&mut self, letters: &str) {
+        for w in self.words.iter_mut() {
+            w.bind_source_letters(letters)
+        }
+    }
 }
 
 impl TypedExpression for FixedLengthExpression {
@@ -0,0 +1,251 @@
+use std::{fmt::Display, rc::Rc, str::FromStr};
+
+use itertools::Itertools;
+use strum::IntoEnumIterator;
+
+use crate::core::prelude::*;
+
+/// A way of giving each letter a numeric value
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub enum Cipher {
+    /// A = 1 to Z = 26, also known as English ordinal
+    #[default]
+    Simple,
+    /// The simple value reduced to a single digit, so J = 1 and S = 1
+    Reduced,
+    /// A = 26 to Z = 1
+    Reverse,
+    /// Values given by the user, indexed by the position of the letter in the alphabet
+    Custom(Box<[u32; 26]>),
+}
+
+impl Cipher {
+    /// The names of all built in ciphers
+    pub const NAMES: [(&'static str, Cipher); 4] = [
+        ("simple", Cipher::Simple),
+        ("ordinal", Cipher::Simple),
+        ("reduced", Cipher::Reduced),
+        ("reverse", Cipher::Reverse),
+    ];
+
+    pub fn letter_value(&self, character: Character) -> u32 {
+        let ordinal = character.ordinal();
+        match self {
+            Cipher::Simple => ordinal,
+            Cipher::Reduced => (ordinal - 1) % 9 + 1,
+            Cipher::Reverse => 27 - ordinal,
+            Cipher::Custom(values) => values[ordinal as usize - 1],
+        }
+    }
+
+    /// The total value of the letters of the text, ignoring everything else.
+    /// Totals too large for a `u32` are capped at `u32::MAX`.
+    pub fn value(&self, text: &str) -> u32 {
+        text.chars()
+            .filter_map(|c| Character::try_from(c).ok())
+            .map(|c| self.letter_value(c))
+            .fold(0, u32::saturating_add)
+    }
+
+    /// The total value of all the words of a solution, capped at `u32::MAX`
+    pub fn solution_value(&self, solution: &ExpressionSolution) -> u32 {
+        solution
+            .homographs
+            .iter()
+            .map(|h| self.value(&h.text))
+            .fold(0, u32::saturating_add)
+    }
+}
+
+impl FromStr for Cipher {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s))
+            .map(|(_, cipher)| cipher.clone())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown cipher - expected one of {}",
+                    Self::NAMES.iter().map(|(name, _)| name).join(", ")
+                )
+            })
+    }
+}
+
+impl Display for Cipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cipher::Simple => Ok(()),
+            Cipher::Custom(values) => {
+                let letters = Character::iter()
+                    .sorted_by_key(|c| c.ordinal())
+                    .filter(|c| values[c.ordinal() as usize - 1] != 0)
+                    .map(|c| {
+                        let value = values[c.ordinal() as usize - 1];
+                        format!("{}={value}", c.as_char().to_ascii_lowercase())
+                    })
+                    .join(" ");
+                write!(f, "({letters})")
+            }
+            _ => {
+                let (name, _) = Self::NAMES
+                    .iter()
+                    .find(|(_, cipher)| cipher == self)
+                    .expect("Every built in cipher has a name");
+                write!(f, "{name}")
+            }
+        }
+    }
+}
+
+/// Words or phrases whose letters add up to a value in a range
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Gematria {
+    pub cipher: Cipher,
+    pub min: u32,
+    pub max: u32,
+}
+
+impl Gematria {
+    pub fn allow(&self, term: &Homograph) -> bool {
+        (self.min..=self.max).contains(&self.cipher.value(&term.text))
+    }
+
+    pub fn allow_solution(&self, solution: &ExpressionSolution) -> bool {
+        (self.min..=self.max).contains(&self.cipher.solution_value(solution))
+    }
+}
+
+impl Display for Gematria {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.min == self.max {
+            write!(f, "${}={}", self.cipher, self.min)
+        } else {
+            write!(f, "${}:{}..{}", self.cipher, self.min, self.max)
+        }
+    }
+}
+
+/// Finds every way of choosing one candidate for each word so that their values add up to a total
+pub struct GematriaIterator<'a> {
+    /// The candidates for each word, sorted by value
+    candidates: Rc<[Vec<(u32, &'a Homograph)>]>,
+    /// For each word chosen so far, the index of the next candidate to try and the end of the candidates to try
+    stack: Vec<(usize, usize)>,
+    /// The total less the values of the words chosen before the last one
+    remaining: u32,
+}
+
+impl<'a> GematriaIterator<'a> {
+    pub fn create(candidates: Rc<[Vec<(u32, &'a Homograph)>]>, total: u32) -> Self {
+        let mut iterator = Self {
+            candidates,
+            stack: vec![],
+            remaining: total,
+        };
+        if !iterator.candidates.is_empty() {
+            iterator.stack.push(iterator.range(0));
+        }
+        iterator
+    }
+
+    /// The candidates for a word which do not go over the remaining total.
+    /// The last word must make up the total exactly.
+    fn range(&self, word: usize) -> (usize, usize) {
+        let candidates = &self.candidates[word];
+        let end = candidates.partition_point(|(v, _)| *v <= self.remaining);
+        let start = if word + 1 == self.candidates.len() {
+            candidates.partition_point(|(v, _)| *v < self.remaining)
+        } else {
+            0
+        };
+        (start, end)
+    }
+}
+
+impl Iterator for GematriaIterator<'_> {
+    type Item = ExpressionSolution;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(&(next, end)) = self.stack.last() {
+            let word = self.stack.len() - 1;
+
+            if next >= end {
+                self.stack.pop();
+                if let Some((parent_next, _)) = self.stack.last() {
+                    self.remaining += self.candidates[word - 1][parent_next - 1].0;
+                }
+                continue;
+            }
+
+            self.stack[word].0 += 1;
+
+            if word + 1 == self.candidates.len() {
+                return Some(ExpressionSolution {
+                    homographs: self
+                        .stack
+                        .iter()
+                        .enumerate()
+                        .map(|(w, (next, _))| self.candidates[w][next - 1].1.clone())
+                        .collect(),
+                });
+            }
+
+            self.remaining -= self.candidates[word][next].0;
+            self.stack.push(self.range(word + 1));
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ntest::test_case;
+
+    const WORDS: &str = "n\tact\t\t\nn\tcat\t\t\nn\tire\t\t\nn\ttic\t\t\nn\tice\t\t\nn\trice\t\t\n";
+
+    #[test_case("simple", "abc", 6, name = "simple")]
+    #[test_case("ordinal", "Zz", 52, name = "ordinal_upper_case")]
+    #[test_case("reduced", "jazz", 18, name = "reduced")]
+    #[test_case("reverse", "a z", 27, name = "reverse_ignores_spaces")]
+    fn test_value(cipher: &str, text: &str, expected: u32) {
+        let cipher = Cipher::from_str(cipher).unwrap();
+
+        assert_eq!(cipher.value(text), expected);
+    }
+
+    #[test]
+    fn test_custom_value() {
+        let mut values = [0; 26];
+        values[0] = 1;
+        values[25] = 10;
+
+        assert_eq!(Cipher::Custom(values.into()).value("jazz"), 21);
+    }
+
+    #[test]
+    fn test_value_does_not_overflow() {
+        let mut values = [0; 26];
+        values[0] = 4_000_000_000;
+
+        assert_eq!(Cipher::Custom(values.into()).value("aa"), u32::MAX);
+    }
+
+    #[test_case("$=24", "act cat", name = "exact")]
+    #[test_case("$:20..35", "act cat ire tic rice", name = "range")]
+    #[test_case("$reverse=57", "act cat", name = "named_cipher")]
+    #[test_case("$(c=3 a=1 t=20)=24", "act cat", name = "custom_cipher")]
+    #[test_case("**($=24)", "act cat", name = "phrase_total")]
+    fn test_gematria_term(input: &str, expected: &str) {
+        let dict = WordContext::from(TermDict::from_csv(WORDS).unwrap());
+        let question = crate::language::prelude::question_parse(input).unwrap();
+
+        let actual = question.solve(&dict).map(|s| s.get_text()).join(" ");
+
+        assert_eq!(actual, expected);
+    }
+}
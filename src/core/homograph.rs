@@ -2,11 +2,14 @@ use std::str::FromStr;
 
 use beef::lean::Cow;
 use enumflags2::{bitflags, BitFlags};
+use itertools::Itertools;
 
 use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 use strum::IntoStaticStr;
 
+use crate::core::prelude::*;
+
 #[derive(Clone, PartialEq, Eq, Serialize)]
 pub struct Homograph {
     pub text: Cow<'static, str>,
@@ -33,6 +36,56 @@ impl Homograph {
             "Unknown Word"
         }
     }
+
+    /// The number of syllables, from the first pronunciation or else from the spelling
+    pub fn syllables(&self) -> usize {
+        match self.pronunciations().next() {
+            Some(ipa) => pronunciation_syllables(ipa),
+            None => spelling_syllables(&self.text),
+        }
+    }
+
+    /// The distinct IPA pronunciations of all meanings
+    pub fn pronunciations(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.meanings
+            .iter()
+            .filter_map(|m| m.pronunciation)
+            .flat_map(|p| p.split_ascii_whitespace())
+            .unique()
+    }
+
+    /// The distinct thesaurus ids of all meanings
+    pub fn synsets(&self) -> impl Iterator<Item = &'static str> + '_ {
+        Self::split_words(self.meanings.iter().filter_map(|m| m.synsets))
+    }
+
+    /// The distinct antonyms of all meanings
+    pub fn antonyms(&self) -> impl Iterator<Item = &'static str> + '_ {
+        Self::split_words(self.meanings.iter().filter_map(|m| m.antonyms))
+    }
+
+    /// The distinct similar words of all meanings
+    pub fn similar(&self) -> impl Iterator<Item = &'static str> + '_ {
+        Self::split_words(self.meanings.iter().filter_map(|m| m.similar))
+    }
+
+    /// Each thesaurus id of all meanings paired with each more general id above it
+    pub fn hypernyms(&self) -> impl Iterator<Item = (&'static str, &'static str)> + '_ {
+        Self::split_words(self.meanings.iter().filter_map(|m| m.hypernyms))
+            .filter_map(|link| link.split_once(':'))
+            .flat_map(|(synset, parents)| parents.split(',').map(move |p| (synset, p)))
+    }
+
+    fn split_words<'a>(
+        lists: impl Iterator<Item = &'static str> + 'a,
+    ) -> impl Iterator<Item = &'static str> + 'a {
+        lists.flat_map(|l| l.split_ascii_whitespace()).unique()
+    }
+
+    /// The emoji of the first meaning which has one
+    pub fn emoji(&self) -> Option<&'static str> {
+        self.meanings.iter().filter_map(|m| m.emoji).next()
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -40,6 +93,18 @@ pub struct Meaning {
     pub part_of_speech: PartOfSpeech,
     pub tags: BitFlags<WordTag>,
     pub definition: Option<&'static str>,
+    /// IPA pronunciations, separated by spaces
+    pub pronunciation: Option<&'static str>,
+    /// An emoji which stands for this meaning
+    pub emoji: Option<&'static str>,
+    /// Thesaurus ids, separated by spaces. Words which share an id are synonyms.
+    pub synsets: Option<&'static str>,
+    /// Words with the opposite meaning, separated by spaces
+    pub antonyms: Option<&'static str>,
+    /// Words with a similar meaning, separated by spaces
+    pub similar: Option<&'static str>,
+    /// The more general thesaurus ids above each of this meaning's ids, such as `02:04,05 03:06`
+    pub hypernyms: Option<&'static str>,
 }
 
 impl PartialOrd for Homograph {
@@ -84,18 +149,66 @@ pub enum WordTag {
     Negative,
 }
 
+impl WordTag {
+    /// The names that can be used to refer to tags in queries
+    pub const NAMES: [(&'static str, WordTag); 4] = [
+        ("masculine", WordTag::Masculine),
+        ("feminine", WordTag::Feminine),
+        ("positive", WordTag::Positive),
+        ("negative", WordTag::Negative),
+    ];
+
+    /// The name used to refer to this tag in queries
+    pub fn name(&self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|(_, tag)| tag == self)
+            .map(|(name, _)| *name)
+            .expect("Every tag has a name")
+    }
+}
+
 impl FromStr for WordTag {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, anyhow::Error> {
-        match s.to_ascii_lowercase().as_str() {
-            "masculine" => Ok(WordTag::Masculine),
-            "feminine" => Ok(WordTag::Feminine),
-            "positive" => Ok(WordTag::Positive),
-            "negative" => Ok(WordTag::Negative),
+        Self::NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s))
+            .map(|(_, tag)| *tag)
+            .ok_or_else(|| anyhow::anyhow!("Could not parse {} as tag", s))
+    }
+}
 
-            _ => anyhow::bail!("Could not parse {} as tag", s),
-        }
+impl PartOfSpeech {
+    /// The names that can be used to refer to parts of speech in queries, shortest first
+    pub const NAMES: [(&'static str, PartOfSpeech); 17] = [
+        ("n", PartOfSpeech::Noun),
+        ("v", PartOfSpeech::Verb),
+        ("j", PartOfSpeech::Adjective),
+        ("a", PartOfSpeech::Adverb),
+        ("p", PartOfSpeech::Preposition),
+        ("l", PartOfSpeech::LastName),  //TODO remove
+        ("f", PartOfSpeech::FirstName), //TODO remove
+        ("noun", PartOfSpeech::Noun),
+        ("verb", PartOfSpeech::Verb),
+        ("adjective", PartOfSpeech::Adjective),
+        ("adverb", PartOfSpeech::Adverb),
+        ("preposition", PartOfSpeech::Preposition),
+        ("firstname", PartOfSpeech::FirstName),
+        ("lastname", PartOfSpeech::LastName),
+        ("interjection", PartOfSpeech::Interjection),
+        ("conjunction", PartOfSpeech::Conjunction),
+        ("pronoun", PartOfSpeech::Pronoun),
+    ];
+
+    /// The shortest name used to refer to this part of speech in queries
+    pub fn short_name(&self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|(_, pos)| pos == self)
+            .map(|(name, _)| *name)
+            .expect("Every part of speech has a name")
     }
 }
 
@@ -103,25 +216,10 @@ impl FromStr for PartOfSpeech {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, anyhow::Error> {
-        match s.to_ascii_lowercase().as_str() {
-            "n" => Ok(PartOfSpeech::Noun),
-            "v" => Ok(PartOfSpeech::Verb),
-            "j" => Ok(PartOfSpeech::Adjective),
-            "a" => Ok(PartOfSpeech::Adverb),
-            "p" => Ok(PartOfSpeech::Preposition),
-
-            "l" => Ok(PartOfSpeech::LastName),  //TODO remove
-            "f" => Ok(PartOfSpeech::FirstName), //TODO remove
-
-            "noun" => Ok(PartOfSpeech::Noun),
-            "verb" => Ok(PartOfSpeech::Verb),
-            "adjective" => Ok(PartOfSpeech::Adjective),
-            "adverb" => Ok(PartOfSpeech::Adverb),
-            "preposition" => Ok(PartOfSpeech::Preposition),
-            "firstname" => Ok(PartOfSpeech::FirstName),
-            "lastname" => Ok(PartOfSpeech::LastName),
-
-            _ => anyhow::bail!("Could not parse {} as part of speech", s),
-        }
+        Self::NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s))
+            .map(|(_, pos)| *pos)
+            .ok_or_else(|| anyhow::anyhow!("Could not parse {} as part of speech", s))
     }
 }
@@ -1,7 +1,8 @@
+use std::{fmt::Display, str::FromStr};
+
 use auto_enums::auto_enum;
 use include_flate::lazy_static;
 use itertools::Itertools;
-use smallvec::SmallVec;
 
 use crate::core::prelude::*;
 use crate::language::prelude::*;
@@ -9,18 +10,60 @@ use crate::language::prelude::*;
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct ManyExpression {
     pub t: ManyExpressionType,
-    pub terms: SmallVec<[WordQueryTerm; 1]>,
+    pub terms: Vec<WordQueryTerm>,
     pub min_words: usize,
     pub max_words: Option<usize>,
+    /// Terms which the phrase as a whole must satisfy, such as a total number of syllables
+    pub constraints: Vec<WordQueryTerm>,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum ManyExpressionType {
     Any,
     Phrase,
+    /// Words which read the same forwards and backwards
+    Palindrome,
+}
+
+impl Display for ManyExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.t)?;
+        for constraint in self.constraints.iter() {
+            write!(f, "({constraint})")?;
+        }
+        for term in self.terms.iter() {
+            write!(f, " + {term}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ManyExpressionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (symbol, _) = Self::SYMBOLS
+            .iter()
+            .find(|(_, t)| t == self)
+            .expect("Every many expression type has a symbol");
+        write!(f, "{symbol}")
+    }
+}
+
+impl FromStr for ManyExpressionType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::SYMBOLS
+            .iter()
+            .find(|(symbol, _)| symbol.eq_ignore_ascii_case(s))
+            .map(|(_, t)| *t)
+            .ok_or_else(|| anyhow::anyhow!("Could not parse {} as many expression", s))
+    }
 }
 
 impl ManyExpression {
+    /// The most words to put in a palindrome when there is no other limit
+    const MAX_PALINDROME_WORDS: usize = 5;
+
     #[auto_enum(Iterator)]
     pub fn solve<'a>(
         &'a self,
@@ -37,6 +80,11 @@ impl ManyExpression {
                 .filter(|pe| self.allow_number_of_words(pe.words.len()))
                 .flat_map(|x| x.solve(dict))
                 .filter(|x| self.allow(x)),
+            ManyExpressionType::Palindrome => PalindromeIterator::create(
+                &dict.reversal_dict,
+                self.max_words.unwrap_or(Self::MAX_PALINDROME_WORDS),
+            )
+            .filter(|x| self.allow(x)),
         }
     }
 
@@ -45,6 +93,7 @@ impl ManyExpression {
             .iter()
             .filter_map(|x| match x {
                 WordQueryTerm::Literal(l) => Some(l.text.len()),
+                WordQueryTerm::Rack(r) => Some(r.key.len()),
                 _ => None,
             })
             .max()
@@ -56,6 +105,25 @@ impl ManyExpression {
             t.upgrade_literals(dict);
         }
     }
+
+    pub fn bind_source_letters(&mut self, letters: &str) {
+        for t in self.terms.iter_mut() {
+            t.bind_source_letters(letters);
+        }
+    }
+
+    pub fn allow_constraints(&self, solution: &ExpressionSolution) -> bool {
+        self.constraints.iter().all(|c| c.allow_phrase(solution))
+    }
+
+    /// Each term must be satisfied by at least one word.
+    /// Negated terms must be satisfied by every word.
+    pub fn allow_terms(&self, solution: &ExpressionSolution) -> bool {
+        self.terms.iter().all(|t| match t {
+            WordQueryTerm::Not(_) => solution.homographs.iter().all(|h| t.allow(h)),
+            _ => solution.homographs.iter().any(|h| t.allow(h)),
+        })
+    }
 }
 
 impl TypedExpression for ManyExpression {
@@ -77,6 +145,9 @@ impl TypedExpression for ManyExpression {
                 ManyExpressionType::Phrase => self
                     .max_words
                     .min(PHRASEEXPRESSIONS.iter().map(|x| x.words.len()).max()),
+                ManyExpressionType::Palindrome => {
+                    Some(self.max_words.unwrap_or(Self::MAX_PALINDROME_WORDS))
+                }
             },
         }
     }
@@ -107,6 +178,7 @@ impl TypedExpression for ManyExpression {
                 .filter(|x| self.allow_number_of_words(x.words.len()))
                 .map(|x| x.count_options(dict))
                 .sum(),
+            ManyExpressionType::Palindrome => None,
         }
     }
 
@@ -116,11 +188,7 @@ impl TypedExpression for ManyExpression {
         if !self.allow_number_of_words(solution.homographs.len()) {
             return None;
         }
-        if !self
-            .terms
-            .iter()
-            .all(|t| solution.homographs.iter().any(|h| t.allow(h)))
-        {
+        if !self.allow_terms(&solution) || !self.allow_constraints(&solution) {
             return None;
         }
 
@@ -130,6 +198,15 @@ impl TypedExpression for ManyExpression {
                 .iter()
                 .filter_map(|pe| pe.order_to_allow(solution.clone())) //TODO remove clone here
                 .next(),
+            ManyExpressionType::Palindrome => solution
+                .homographs
+                .iter()
+                .cloned()
+                .permutations(solution.homographs.len())
+                .map(|homographs| ExpressionSolution {
+                    homographs: homographs.into(),
+                })
+                .find(|x| is_palindrome(&x.get_text())),
         }
     }
 
@@ -137,28 +214,33 @@ impl TypedExpression for ManyExpression {
         if !self.allow_number_of_words(solution.homographs.len()) {
             return false;
         }
-        if !self
-            .terms
-            .iter()
-            .all(|t| solution.homographs.iter().any(|h| t.allow(h)))
-        {
+        if !self.allow_terms(solution) || !self.allow_constraints(solution) {
             return false;
         }
 
         match self.t {
             ManyExpressionType::Any => true,
             ManyExpressionType::Phrase => PHRASEEXPRESSIONS.iter().any(|pe| pe.allow(solution)),
+            ManyExpressionType::Palindrome => is_palindrome(&solution.get_text()),
         }
     }
 }
 
 impl ManyExpressionType {
+    /// The symbols of all many expression types
+    pub const SYMBOLS: [(&'static str, ManyExpressionType); 3] = [
+        ("**", ManyExpressionType::Any),
+        ("!phrase", ManyExpressionType::Phrase),
+        ("!palindrome", ManyExpressionType::Palindrome),
+    ];
+
     pub fn allow(&self, solution: &ExpressionSolution) -> bool {
         //log::info!("Possible Solution: {:?}", solution);
 
         match self {
             ManyExpressionType::Any => true,
             ManyExpressionType::Phrase => PHRASEEXPRESSIONS.iter().any(|fle| fle.allow(solution)),
+            ManyExpressionType::Palindrome => is_palindrome(&solution.get_text()),
         }
     }
 }
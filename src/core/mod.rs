@@ -4,17 +4,29 @@ mod anagram_key;
 mod anagram_settings;
 mod binary_map;
 mod character;
+mod contains_letters;
+mod definition_index;
+mod edit_distance;
 mod equation;
 mod expression;
 mod fixed_length_expression;
+mod gematria;
 mod homograph;
 mod many_expression_type;
+mod palindrome_iterator;
 mod pattern;
+mod pronunciation;
 mod question;
+mod rack;
+mod reversal_dict;
 mod solution;
 mod solvable;
+mod sub_anagram_iterator;
+mod taxonomy;
 mod term_dict;
+mod thesaurus;
 mod word_context;
+mod word_ladder;
 mod word_query;
 
 pub mod prelude {
@@ -24,17 +36,29 @@ pub mod prelude {
     pub use crate::core::anagram_settings::*;
     pub use crate::core::binary_map::*;
     pub use crate::core::character::*;
+    pub use crate::core::contains_letters::*;
+    pub use crate::core::definition_index::*;
+    pub use crate::core::edit_distance::*;
 
     pub use crate::core::equation::*;
     pub use crate::core::expression::*;
     pub use crate::core::fixed_length_expression::*;
+    pub use crate::core::gematria::*;
     pub use crate::core::homograph::*;
     pub use crate::core::many_expression_type::*;
+    pub use crate::core::palindrome_iterator::*;
     pub use crate::core::pattern::*;
+    pub use crate::core::pronunciation::*;
     pub use crate::core::question::*;
+    pub use crate::core::rack::*;
+    pub use crate::core::reversal_dict::*;
     pub use crate::core::solution::*;
     pub use crate::core::solvable::*;
+    pub use crate::core::sub_anagram_iterator::*;
+    pub use crate::core::taxonomy::*;
     pub use crate::core::term_dict::*;
+    pub use crate::core::thesaurus::*;
     pub use crate::core::word_context::*;
+    pub use crate::core::word_ladder::*;
     pub use crate::core::word_query::*;
 }
@@ -0,0 +1,208 @@
+use smallvec::SmallVec;
+
+use crate::core::prelude::*;
+
+/// Whether the letters of the text read the same in both directions
+pub fn is_palindrome(text: &str) -> bool {
+    ReversalDict::letters(text).eq(ReversalDict::letters(text).rev())
+}
+
+/// Letters which have been used on one side of the palindrome but not yet mirrored on the other
+#[derive(Clone, Debug)]
+enum Excess {
+    /// Letters at the end of the left words which the right words must spell backwards
+    Left(String),
+    /// Letters at the start of the right words, reversed, which the left words must spell
+    Right(String),
+}
+
+impl Excess {
+    fn is_palindrome(&self) -> bool {
+        match self {
+            Excess::Left(s) | Excess::Right(s) => s.chars().eq(s.chars().rev()),
+        }
+    }
+}
+
+struct PalindromeState<'a> {
+    left: SmallVec<[&'a Homograph; 4]>,
+    right: SmallVec<[&'a Homograph; 4]>,
+    excess: Excess,
+}
+
+/// Finds sequences of words which read the same forwards and backwards.
+/// Words are added to the left from the forwards index and to the right from the reversed index,
+/// whichever side is behind, until the letters left over in the middle are a palindrome.
+pub struct PalindromeIterator<'a> {
+    dict: &'a ReversalDict,
+    stack: Vec<PalindromeState<'a>>,
+    max_words: usize,
+}
+
+impl<'a> PalindromeIterator<'a> {
+    pub fn create(dict: &'a ReversalDict, max_words: usize) -> Self {
+        let root = PalindromeState {
+            left: Default::default(),
+            right: Default::default(),
+            excess: Excess::Right(String::new()),
+        };
+
+        Self {
+            dict,
+            stack: vec![root],
+            max_words,
+        }
+    }
+
+    fn push_children(&mut self, state: &PalindromeState<'a>) {
+        let dict = self.dict;
+        let start = self.stack.len();
+
+        match &state.excess {
+            Excess::Left(s) => {
+                for (key, homographs) in dict.compatible(&dict.words, s) {
+                    let excess = if key.len() <= s.len() {
+                        Excess::Left(s[key.len()..].to_string())
+                    } else {
+                        Excess::Right(key[s.len()..].to_string())
+                    };
+                    for homograph in homographs {
+                        let mut right = state.right.clone();
+                        right.push(homograph);
+                        self.stack.push(PalindromeState {
+                            left: state.left.clone(),
+                            right,
+                            excess: excess.clone(),
+                        });
+                    }
+                }
+            }
+            Excess::Right(t) => {
+                for (key, homographs) in dict.compatible(&dict.forwards, t) {
+                    let excess = if key.len() <= t.len() {
+                        Excess::Right(t[key.len()..].to_string())
+                    } else {
+                        Excess::Left(key[t.len()..].to_string())
+                    };
+                    for homograph in homographs {
+                        let mut left = state.left.clone();
+                        left.push(homograph);
+                        self.stack.push(PalindromeState {
+                            left,
+                            right: state.right.clone(),
+                            excess: excess.clone(),
+                        });
+                    }
+                }
+            }
+        }
+
+        //The stack is popped from the end so put the first child last
+        self.stack[start..].reverse();
+    }
+}
+
+impl<'a> Iterator for PalindromeIterator<'a> {
+    type Item = ExpressionSolution;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(state) = self.stack.pop() {
+            let words = state.left.len() + state.right.len();
+            if words < self.max_words {
+                self.push_children(&state);
+            }
+
+            if words > 0 && state.excess.is_palindrome() {
+                return Some(ExpressionSolution {
+                    homographs: state
+                        .left
+                        .into_iter()
+                        .chain(state.right.into_iter().rev())
+                        .cloned()
+                        .collect(),
+                });
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::core::prelude::*;
+    use ntest::test_case;
+
+    #[test_case("level", 3, "level; level level; level level level", name = "one_word")]
+    #[test_case("stop pots", 2, "pots stop; stop pots", name = "two_words")]
+    #[test_case("cat dog", 3, "", name = "none")]
+    fn test_palindromes(words: &str, max_words: usize, expected: &str) {
+        let words = words.split_ascii_whitespace().map(|text| Homograph {
+            text: text.into(),
+            is_single_word: true,
+            meanings: Default::default(),
+        });
+        let dict = ReversalDict::from(words);
+
+        let actual = PalindromeIterator::create(&dict, max_words)
+            .map(|s| s.get_text())
+            .join("; ");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_panama() {
+        let words = "a man plan canal panama"
+            .split_ascii_whitespace()
+            .map(|text| Homograph {
+                text: text.into(),
+                is_single_word: true,
+                meanings: Default::default(),
+            });
+        let dict = ReversalDict::from(words);
+
+        let found = PalindromeIterator::create(&dict, 7)
+            .any(|s| s.get_text() == "a man a plan a canal panama");
+
+        assert!(found);
+    }
+
+    #[test_case("Step on no pets", true, name = "phrase")]
+    #[test_case("palindrome", false, name = "not_palindrome")]
+    fn test_is_palindrome(text: &str, expected: bool) {
+        assert_eq!(is_palindrome(text), expected);
+    }
+
+    #[test_case(
+        "!palindrome + #v",
+        "level pots stop level; level pots level stop level",
+        name = "with_verb"
+    )]
+    #[test_case(
+        "!palindrome + -level",
+        "pots stop; pots pots stop stop",
+        name = "without_level"
+    )]
+    #[test_case(
+        "[stoppots] =a !palindrome",
+        "stoppots : pots stop; stoppots : stop pots",
+        name = "anagram"
+    )]
+    fn test_palindrome_question(input: &str, expected: &str) {
+        let dict = WordContext::from(
+            TermDict::from_csv("n\tpots\t\t\nn\tlevel\t\t\nv\tstop\t\t\n").unwrap(),
+        );
+        let question = crate::language::prelude::question_parse(input).unwrap();
+
+        let actual = question
+            .solve(&dict)
+            .take(2)
+            .map(|s| s.get_text())
+            .join("; ");
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::core::prelude::*;
 use itertools::Itertools;
-use regex::Regex;
-use std::str::FromStr;
+use regex::{Regex, RegexBuilder};
+use std::{fmt::Display, str::FromStr};
 
 #[derive(Clone, Debug)]
 pub struct Pattern {
@@ -9,14 +9,16 @@ pub struct Pattern {
     pub regex: Regex,
 }
 
-impl From<Vec<PatternComponent>> for Pattern {
-    fn from(components: Vec<PatternComponent>) -> Self {
+impl TryFrom<Vec<PatternComponent>> for Pattern {
+    type Error = regex::Error;
+
+    fn try_from(components: Vec<PatternComponent>) -> Result<Self, Self::Error> {
         let regex_str =
             "^(?i)".to_owned() + &components.iter().map(|x| x.regex_str()).join("") + "$";
 
-        let regex = Regex::new(regex_str.as_str()).unwrap();
+        let regex = Regex::new(regex_str.as_str())?;
 
-        Pattern { components, regex }
+        Ok(Pattern { components, regex })
     }
 }
 
@@ -28,7 +30,166 @@ impl PartialEq for Pattern {
 
 impl Eq for Pattern {}
 
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for component in self.components.iter() {
+            write!(f, "{component}")?;
+        }
+        Ok(())
+    }
+}
+
 impl Pattern {
+    pub fn allow(&self, term: &Homograph) -> bool {
+        if !self.regex.is_match(&term.text) {
+            return false;
+        }
+        if !self.needs_backtracking() {
+            return true;
+        }
+
+        let chars = term.text.chars().collect_vec();
+        Self::matches_from(&self.components, &chars, 0, &mut vec![])
+    }
+
+    /// Whether some components depend on the rest of the word, so the regex alone is not enough
+    fn needs_backtracking(&self) -> bool {
+        self.components.iter().any(|c| c.is_contextual())
+    }
+
+    /// Whether the components match the characters from the position onwards.
+    /// `variables` holds the letters already assigned to each letter variable.
+    fn matches_from(
+        components: &[PatternComponent],
+        chars: &[char],
+        position: usize,
+        variables: &mut Vec<(usize, char)>,
+    ) -> bool {
+        let Some((first, rest)) = components.split_first() else {
+            return position == chars.len();
+        };
+
+        match first {
+            PatternComponent::Any => (position..=chars.len())
+                .take_while(|end| chars[position..*end].iter().all(|c| c.is_alphabetic()))
+                .any(|end| Self::matches_from(rest, chars, end, variables)),
+            PatternComponent::AnyChar(len) => {
+                let end = position + len;
+                end <= chars.len()
+                    && chars[position..end].iter().all(|c| c.is_alphabetic())
+                    && Self::matches_from(rest, chars, end, variables)
+            }
+            PatternComponent::Literal(s) => {
+                let end = position + s.chars().count();
+                end <= chars.len()
+                    && chars[position..end]
+                        .iter()
+                        .zip(s.chars())
+                        .all(|(a, b)| a.eq_ignore_ascii_case(&b))
+                    && Self::matches_from(rest, chars, end, variables)
+            }
+            PatternComponent::CharacterClass(class) => {
+                position < chars.len()
+                    && class.allow(chars[position], &chars[..position])
+                    && Self::matches_from(rest, chars, position + 1, variables)
+            }
+            PatternComponent::Variable(number) => {
+                let Some(c) = chars.get(position).map(|c| c.to_ascii_lowercase()) else {
+                    return false;
+                };
+                if !c.is_alphabetic() {
+                    return false;
+                }
+
+                match variables.iter().find(|(n, _)| n == number) {
+                    Some((_, assigned)) => {
+                        *assigned == c && Self::matches_from(rest, chars, position + 1, variables)
+                    }
+                    None => {
+                        if variables.iter().any(|(_, assigned)| *assigned == c) {
+                            return false;
+                        }
+                        variables.push((*number, c));
+                        let result = Self::matches_from(rest, chars, position + 1, variables);
+                        variables.pop();
+                        result
+                    }
+                }
+            }
+            PatternComponent::Repeat {
+                component,
+                min,
+                max,
+            } => {
+                //Every component that can be repeated uses at least one character
+                let most = max
+                    .unwrap_or(usize::MAX)
+                    .min(chars.len().saturating_sub(position));
+
+                (*min..=most).any(|count| {
+                    let expanded = std::iter::repeat_n(component.as_ref().clone(), count)
+                        .chain(rest.iter().cloned())
+                        .collect_vec();
+                    Self::matches_from(&expanded, chars, position, variables)
+                })
+            }
+        }
+    }
+
+    /// Restrict any source letter classes to the given letters
+    pub fn bind_source_letters(&mut self, letters: &str) {
+        let mut changed = false;
+        for component in self.components.iter_mut() {
+            if let PatternComponent::CharacterClass(CharacterClass::Source(source)) = component {
+                *source = Some(letters.to_string());
+                changed = true;
+            }
+        }
+
+        if changed {
+            if let Ok(pattern) = Pattern::try_from(self.components.clone()) {
+                *self = pattern;
+            }
+        }
+    }
+}
+
+/// A regular expression written directly in a query.
+/// It is matched case insensitively against the text of each word and is not anchored.
+#[derive(Clone, Debug)]
+pub struct RawRegex {
+    pub source: String,
+    pub regex: Regex,
+}
+
+impl FromStr for RawRegex {
+    type Err = regex::Error;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let regex = RegexBuilder::new(source).case_insensitive(true).build()?;
+
+        Ok(RawRegex {
+            source: source.to_string(),
+            regex,
+        })
+    }
+}
+
+impl PartialEq for RawRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for RawRegex {}
+
+impl Display for RawRegex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "/{}/", self.source.replace('/', "\\/"))
+    }
+}
+
+impl RawRegex {
     pub fn allow(&self, term: &Homograph) -> bool {
         self.regex.is_match(&term.text)
     }
@@ -40,6 +201,15 @@ pub enum PatternComponent {
     AnyChar(usize),
     Literal(String),
     CharacterClass(CharacterClass),
+    /// A numbered letter.
+    /// Variables with the same number are the same letter and variables with different numbers are different letters.
+    Variable(usize),
+    /// A component repeated between `min` and `max` times
+    Repeat {
+        component: Box<PatternComponent>,
+        min: usize,
+        max: Option<usize>,
+    },
 }
 
 impl PatternComponent {
@@ -48,7 +218,50 @@ impl PatternComponent {
             PatternComponent::Any => "[[:alpha:]]*".to_string(),
             PatternComponent::AnyChar(len) => format!("[[:alpha:]]{{{len}}}"),
             PatternComponent::Literal(s) => s.clone(),
-            PatternComponent::CharacterClass(c) => c.regex_char().to_string(),
+            PatternComponent::CharacterClass(c) => c.regex_char(),
+            PatternComponent::Variable(_) => "[[:alpha:]]".to_string(),
+            PatternComponent::Repeat {
+                component,
+                min,
+                max,
+            } => {
+                let inner = component.regex_str();
+                match max {
+                    Some(max) => format!("(?:{inner}){{{min},{max}}}"),
+                    None => format!("(?:{inner}){{{min},}}"),
+                }
+            }
+        }
+    }
+
+    /// Whether matching this component depends on the rest of the word
+    fn is_contextual(&self) -> bool {
+        match self {
+            PatternComponent::CharacterClass(CharacterClass::Unused)
+            | PatternComponent::Variable(_) => true,
+            PatternComponent::Repeat { component, .. } => component.is_contextual(),
+            _ => false,
+        }
+    }
+}
+
+impl Display for PatternComponent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatternComponent::Any => write!(f, "*"),
+            PatternComponent::AnyChar(len) => write!(f, "{}", "?".repeat(*len)),
+            PatternComponent::Literal(s) => write!(f, "{s}"),
+            PatternComponent::CharacterClass(c) => write!(f, "{c}"),
+            PatternComponent::Variable(n) => write!(f, "[{n}]"),
+            PatternComponent::Repeat {
+                component,
+                min,
+                max,
+            } => match (min, max) {
+                (min, None) => write!(f, "{component}{{{min},}}"),
+                (min, Some(max)) if min == max => write!(f, "{component}{{{min}}}"),
+                (min, Some(max)) => write!(f, "{component}{{{min},{max}}}"),
+            },
         }
     }
 }
@@ -57,24 +270,279 @@ impl PatternComponent {
 pub enum CharacterClass {
     Vowel,
     Consonant,
+    /// A vowel, counting y as a vowel
+    VowelOrY,
+    /// A letter which does not appear earlier in the word
+    Unused,
+    /// A letter from the literals on the other side of an equation.
+    /// Matches any letter until the source is known.
+    Source(Option<String>),
+    /// One of a set of letters, or any letter not in the set
+    Set {
+        letters: String,
+        negated: bool,
+    },
 }
 impl CharacterClass {
-    pub fn regex_char(&self) -> &'static str {
+    /// The symbols of all named character classes
+    pub const SYMBOLS: [(&'static str, CharacterClass); 5] = [
+        ("@v", CharacterClass::Vowel),
+        ("@c", CharacterClass::Consonant),
+        ("@y", CharacterClass::VowelOrY),
+        ("@u", CharacterClass::Unused),
+        ("@s", CharacterClass::Source(None)),
+    ];
+
+    const VOWELS: &'static str = "aeiou";
+    const VOWELS_AND_Y: &'static str = "aeiouy";
+    const CONSONANTS: &'static str = "bcdfghjklmnpqrstvwxyz";
+
+    pub fn regex_char(&self) -> String {
         match self {
-            CharacterClass::Vowel => "[aeiou]",
-            CharacterClass::Consonant => "[bcdfghjklmnpqrstvwxyz]",
+            CharacterClass::Vowel => format!("[{}]", Self::VOWELS),
+            CharacterClass::Consonant => format!("[{}]", Self::CONSONANTS),
+            CharacterClass::VowelOrY => format!("[{}]", Self::VOWELS_AND_Y),
+            CharacterClass::Unused | CharacterClass::Source(None) => "[[:alpha:]]".to_string(),
+            CharacterClass::Source(Some(letters)) => format!("[{letters}]"),
+            CharacterClass::Set {
+                letters,
+                negated: false,
+            } => format!("[{letters}]"),
+            CharacterClass::Set {
+                letters,
+                negated: true,
+            } => format!("[[:alpha:]&&[^{letters}]]"),
         }
     }
+
+    /// Whether the character is in this class, given the characters before it in the word
+    pub fn allow(&self, c: char, previous: &[char]) -> bool {
+        if !c.is_alphabetic() {
+            return false;
+        }
+        let lower = c.to_ascii_lowercase();
+
+        match self {
+            CharacterClass::Vowel => Self::VOWELS.contains(lower),
+            CharacterClass::Consonant => Self::CONSONANTS.contains(lower),
+            CharacterClass::VowelOrY => Self::VOWELS_AND_Y.contains(lower),
+            CharacterClass::Unused => !previous.iter().any(|p| p.eq_ignore_ascii_case(&c)),
+            CharacterClass::Source(None) => true,
+            CharacterClass::Source(Some(letters)) => letters.contains(lower),
+            CharacterClass::Set { letters, negated } => letters.contains(lower) != *negated,
+        }
+    }
+
+    /// Parse the text of a set after its opening bracket
+    pub fn parse_set(s: &str) -> Result<Self, anyhow::Error> {
+        let Some(inner) = s.strip_suffix(']') else {
+            anyhow::bail!("The character set @[{s} is missing a closing ]");
+        };
+        let (negated, inner) = match inner.strip_prefix('^') {
+            Some(rest) => (true, rest),
+            None => (false, inner),
+        };
+
+        if let Some(c) = inner.chars().find(|c| !c.is_ascii_alphabetic()) {
+            anyhow::bail!("Character sets can only contain letters, not '{c}'");
+        }
+        if inner.is_empty() {
+            anyhow::bail!("The character set @[{s} is empty");
+        }
+
+        let letters: String = inner
+            .chars()
+            .map(|c| c.to_ascii_lowercase())
+            .sorted()
+            .dedup()
+            .collect();
+
+        Ok(CharacterClass::Set { letters, negated })
+    }
+}
+
+impl Display for CharacterClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let CharacterClass::Set { letters, negated } = self {
+            return write!(f, "@[{}{letters}]", if *negated { "^" } else { "" });
+        }
+
+        // Compare variants only, so a source with known letters is still @s
+        let (symbol, _) = Self::SYMBOLS
+            .iter()
+            .find(|(_, class)| std::mem::discriminant(class) == std::mem::discriminant(self))
+            .expect("Every named character class has a symbol");
+        write!(f, "{symbol}")
+    }
 }
 
 impl FromStr for CharacterClass {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, anyhow::Error> {
-        match s.to_ascii_lowercase().as_str() {
-            "@v" => Ok(CharacterClass::Vowel),
-            "@c" => Ok(CharacterClass::Consonant),
-            _ => anyhow::bail!("The only valid character classes are @v and @c"),
+        if let Some(set) = s.strip_prefix("@[") {
+            return Self::parse_set(set);
         }
+
+        Self::SYMBOLS
+            .iter()
+            .find(|(symbol, _)| symbol.eq_ignore_ascii_case(s))
+            .map(|(_, class)| class.clone())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "The valid character classes are {} and sets such as @[rstlne]",
+                    Self::SYMBOLS.iter().map(|(symbol, _)| symbol).join(", ")
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::prelude::*;
+    use crate::language::prelude::*;
+    use ntest::test_case;
+
+    fn parse_pattern(input: &str) -> Pattern {
+        match question_parse(input).unwrap() {
+            Question::Expression(Expression::FixedLength(fle)) => {
+                match fle.words[0].terms[0].terms[0].clone() {
+                    WordQueryTerm::Pattern(p) => p,
+                    _ => unreachable!(),
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    fn homograph(text: &'static str) -> Homograph {
+        Homograph {
+            text: text.into(),
+            is_single_word: true,
+            meanings: Default::default(),
+        }
+    }
+
+    #[test_case("@[rstlne]at", "rat", true, name = "set")]
+    #[test_case("@[rstlne]at", "cat", false, name = "set_mismatch")]
+    #[test_case("c@[^aeiou]t", "cyt", true, name = "negated_set")]
+    #[test_case("c@[^aeiou]t", "cat", false, name = "negated_set_mismatch")]
+    #[test_case("b@y", "by", true, name = "y_as_vowel")]
+    #[test_case("b@v", "by", false, name = "y_not_vowel")]
+    #[test_case("@u@u@u", "cat", true, name = "unused")]
+    #[test_case("@u@u@u", "tot", false, name = "unused_repeated")]
+    #[test_case("*@u*", "banana", true, name = "unused_backtracks")]
+    #[test_case("*@u", "banana", false, name = "unused_at_end")]
+    #[test_case("@s@s@s", "cat", true, name = "unbound_source")]
+    #[test_case("[1][2][2][1]", "abba", true, name = "abba")]
+    #[test_case("[1][2][2][1]", "AbbA", true, name = "abba_case_insensitive")]
+    #[test_case("[1][2][2][1]", "aaaa", false, name = "distinct_variables")]
+    #[test_case("[1][2][2][1]", "abbc", false, name = "same_variable")]
+    #[test_case("[1][2][3][3][2]", "level", false, name = "crypto_mismatch")]
+    #[test_case("[1][2][3][3][2]", "sheee", false, name = "crypto_distinct")]
+    #[test_case("[1][2][3][3][2]", "spoop", true, name = "crypto")]
+    #[test_case("*[1]?[1]", "banana", true, name = "mixed_any")]
+    #[test_case("t[1][1]?", "teem", true, name = "mixed_literal")]
+    #[test_case("t[1][1]?", "them", false, name = "mixed_literal_mismatch")]
+    #[test_case("b@v{2,3}t", "boot", true, name = "range_quantifier")]
+    #[test_case("b@v{2,3}t", "bot", false, name = "range_quantifier_too_few")]
+    #[test_case("b@v{2,3}t", "beaout", false, name = "range_quantifier_too_many")]
+    #[test_case("@c+a", "stra", true, name = "plus_quantifier")]
+    #[test_case("@c+a", "a", false, name = "plus_quantifier_empty")]
+    #[test_case("@c+y", "dry", true, name = "plus_quantifier_before_literal")]
+    #[test_case("c@v+t", "coat", true, name = "plus_quantifier_inside")]
+    #[test_case("c@v+t", "ct", false, name = "plus_quantifier_inside_empty")]
+    #[test_case("a@c+", "astr", true, name = "plus_quantifier_at_end")]
+    #[test_case("@c{1,}a", "stra", true, name = "open_quantifier_before_literal")]
+    #[test_case("?{4}", "abcd", true, name = "exact_quantifier")]
+    #[test_case("?{4}", "abc", false, name = "exact_quantifier_mismatch")]
+    #[test_case("??{2,}", "abc", true, name = "open_quantifier")]
+    #[test_case("??{2,}", "ab", false, name = "open_quantifier_too_few")]
+    #[test_case("??{2}", "abc", true, name = "quantifies_last_question_mark")]
+    #[test_case("??{2}", "abcd", false, name = "quantifies_last_question_mark_only")]
+    #[test_case("@u{3}", "cat", true, name = "quantified_unused")]
+    #[test_case("@u{3}", "tot", false, name = "quantified_unused_repeated")]
+    fn test_character_class(input: &str, word: &str, expected: bool) {
+        let pattern = parse_pattern(input);
+
+        assert_eq!(pattern.allow(&homograph(word)), expected);
+    }
+
+    #[test_case("@[TsrT]*", "@[rst]*", name = "normalised_set")]
+    #[test_case("@[^uoiea]*", "@[^aeiou]*", name = "normalised_negated_set")]
+    #[test_case("[1]o[1]", "[1]o[1]", name = "variables")]
+    #[test_case(
+        "@v{2,3}@c{1,}?{4}??{2,}",
+        "@v{2,3}@c{1,}?{4}??{2,}",
+        name = "quantifiers"
+    )]
+    #[test_case("@c+", "@c{1,}", name = "plus_quantifier_display")]
+    fn test_display(input: &str, expected: &str) {
+        assert_eq!(parse_pattern(input).to_string(), expected);
+    }
+
+    #[test]
+    fn test_source_letters() {
+        let equation = match question_parse("act =a @s@s@s").unwrap() {
+            Question::Equation(eq) => *eq,
+            _ => unreachable!(),
+        };
+
+        let Expression::FixedLength(right) = equation.right else {
+            unreachable!()
+        };
+        let query = &right.words[0];
+
+        assert!(query.allow(&homograph("cat")));
+        assert!(!query.allow(&homograph("dog")));
+        assert_eq!(query.to_string(), "@s@s@s");
+    }
+
+    #[test_case(
+        "c@[ab1]t",
+        "Character sets can only contain letters, not '1'",
+        name = "not_letter"
+    )]
+    #[test_case("c@[]t", "The character set @[] is empty", name = "empty")]
+    #[test_case("c@[^]t", "The character set @[^] is empty", name = "empty_negated")]
+    #[test_case(
+        "c@[ab",
+        "The character set @[ab is missing a closing ]",
+        name = "unclosed"
+    )]
+    fn test_malformed_set(input: &str, expected: &str) {
+        let diagnostic = question_parse(input).unwrap_err();
+
+        assert_eq!(diagnostic.message, expected);
+    }
+
+    #[test_case("@c+ #n", 2, name = "plus_then_space_is_conjunction")]
+    #[test_case("@c+#n", 2, name = "plus_then_term_is_conjunction")]
+    #[test_case("@c+a", 1, name = "plus_then_pattern_is_quantifier")]
+    #[test_case("@c+y", 1, name = "plus_then_literal_is_quantifier")]
+    #[test_case("c@v+t", 1, name = "plus_inside_pattern_is_quantifier")]
+    #[test_case("c???+*e*", 1, name = "plus_without_spaces_is_quantifier")]
+    #[test_case("c??? + *e*", 2, name = "plus_with_spaces_is_conjunction")]
+    #[test_case("@c+", 1, name = "plus_at_end_is_quantifier")]
+    #[test_case("(@v+) 5", 1, name = "plus_before_bracket_is_quantifier")]
+    fn test_plus(input: &str, expected_disjunctions: usize) {
+        let question = question_parse(input).unwrap();
+        let Question::Expression(Expression::FixedLength(fle)) = question else {
+            unreachable!()
+        };
+        let query = match fle.words[0].terms[0].terms[0].clone() {
+            WordQueryTerm::Nested(n) => *n,
+            _ => fle.words[0].clone(),
+        };
+
+        assert_eq!(query.terms.len(), expected_disjunctions);
+    }
+
+    #[test_case("b@v{3,2}t", 1, 8, name = "min_more_than_max")]
+    #[test_case("b@v{0}t", 1, 6, name = "zero_repetitions")]
+    fn test_invalid_quantifier(input: &str, start: usize, end: usize) {
+        let diagnostic = question_parse(input).unwrap_err();
+
+        assert_eq!(diagnostic.span, start..end);
     }
 }
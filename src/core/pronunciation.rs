@@ -0,0 +1,424 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+};
+
+use itertools::Itertools;
+use smallvec::SmallVec;
+
+use crate::core::prelude::*;
+
+const IPA_VOWELS: &str = "aeiouyæɑɒɔəɛɜɪʊʌɐøœɨʉɯɤɘɵɞʏɚɝ";
+const IPA_STRESS: char = 'ˈ';
+/// Marks which do not change the sound of a rhyme
+const IPA_IGNORED: &str = "ˈˌ.ˑ/[]()";
+
+const SPELLING_VOWELS: &str = "aeiouy";
+/// Marks a consonant which forms a syllable on its own, as in button
+const IPA_SYLLABIC: char = '\u{0329}';
+
+/// The sounds at the end of a word, used to decide whether words rhyme.
+/// Pronunciations are compared if both words have them, otherwise spellings are compared.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct RhymeKeys {
+    pub pronunciations: SmallVec<[String; 1]>,
+    pub spelling: Option<String>,
+}
+
+impl RhymeKeys {
+    pub fn for_homograph(homograph: &Homograph) -> Self {
+        Self {
+            pronunciations: homograph
+                .pronunciations()
+                .filter_map(pronunciation_rhyme)
+                .unique()
+                .collect(),
+            spelling: spelling_rhyme(&homograph.text),
+        }
+    }
+
+    pub fn rhymes_with(&self, other: &Self) -> bool {
+        if !self.pronunciations.is_empty() && !other.pronunciations.is_empty() {
+            self.pronunciations
+                .iter()
+                .any(|p| other.pronunciations.contains(p))
+        } else {
+            self.spelling.is_some() && self.spelling == other.spelling
+        }
+    }
+}
+
+/// The final stressed vowel and everything after it
+pub fn pronunciation_rhyme(ipa: &str) -> Option<String> {
+    let stressed = ipa
+        .rfind(IPA_STRESS)
+        .map(|i| &ipa[i + IPA_STRESS.len_utf8()..])
+        .unwrap_or(ipa);
+    let start = stressed.find(|c| IPA_VOWELS.contains(c))?;
+
+    Some(
+        stressed[start..]
+            .chars()
+            .filter(|c| !IPA_IGNORED.contains(*c))
+            .collect(),
+    )
+}
+
+/// The last group of vowels and everything after it, skipping a silent e
+pub fn spelling_rhyme(text: &str) -> Option<String> {
+    let chars = text
+        .chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_lowercase())
+        .collect_vec();
+    let is_vowel = |c: &char| SPELLING_VOWELS.contains(*c);
+
+    let search_end = match chars.as_slice() {
+        [.., penultimate, 'e'] if chars.len() > 2 && !is_vowel(penultimate) => chars.len() - 2,
+        _ => chars.len(),
+    };
+
+    let last_vowel = chars[..search_end].iter().rposition(is_vowel)?;
+    let start = chars[..last_vowel]
+        .iter()
+        .rposition(|c| !is_vowel(c))
+        .map(|i| i + 1)
+        .unwrap_or(0);
+
+    Some(chars[start..].iter().collect())
+}
+
+/// The number of vowel sounds and syllabic consonants in a pronunciation
+pub fn pronunciation_syllables(ipa: &str) -> usize {
+    let vowel_groups = ipa
+        .chars()
+        .filter(|c| !IPA_IGNORED.contains(*c) && *c != 'ː')
+        .group_by(|c| IPA_VOWELS.contains(*c))
+        .into_iter()
+        .filter(|(is_vowel, _)| *is_vowel)
+        .count();
+
+    vowel_groups + ipa.matches(IPA_SYLLABIC).count()
+}
+
+/// The number of vowel groups in each word, skipping silent e, with at least one per word
+pub fn spelling_syllables(text: &str) -> usize {
+    text.split(|c: char| !c.is_ascii_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let chars = word.to_ascii_lowercase().chars().collect_vec();
+            let is_vowel = |c: &char| SPELLING_VOWELS.contains(*c);
+
+            let vowel_groups = chars
+                .iter()
+                .group_by(|c| is_vowel(c))
+                .into_iter()
+                .filter(|(v, _)| *v)
+                .count();
+
+            let silent_e = match chars.as_slice() {
+                [.., 'l', 'e'] => false,
+                [.., a, b, 'e'] => !is_vowel(b) && (is_vowel(a) || chars.len() > 3),
+                _ => false,
+            };
+
+            (vowel_groups - usize::from(silent_e && vowel_groups > 1)).max(1)
+        })
+        .sum()
+}
+
+/// How some words sound, used to find words which sound alike.
+/// Pronunciations are compared if both sides have them, otherwise spellings are compared.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct SoundKeys {
+    pub pronunciation: Option<String>,
+    pub spelling: String,
+}
+
+impl SoundKeys {
+    pub fn for_homographs<'a>(homographs: impl IntoIterator<Item = &'a Homograph>) -> Self {
+        let homographs = homographs.into_iter().collect_vec();
+
+        Self {
+            pronunciation: homographs
+                .iter()
+                .map(|h| h.pronunciations().next().map(pronunciation_sounds))
+                .collect(),
+            spelling: homographs
+                .iter()
+                .map(|h| spelling_sounds(&h.text))
+                .collect(),
+        }
+    }
+
+    /// The number of sounds which differ, if the words sound alike.
+    /// A quarter of the sounds of the shorter key may differ, and at least one.
+    pub fn sounds_like(&self, other: &Self) -> Option<usize> {
+        let (a, b) = match (&self.pronunciation, &other.pronunciation) {
+            (Some(a), Some(b)) => (a, b),
+            _ => (&self.spelling, &other.spelling),
+        };
+        let (a_len, b_len) = (a.chars().count(), b.chars().count());
+        let tolerance = (a_len.min(b_len) / 4).max(1);
+
+        if a_len.abs_diff(b_len) > tolerance {
+            return None;
+        }
+
+        let distance = edit_distance(a, b);
+        (distance <= tolerance).then_some(distance)
+    }
+}
+
+/// Words grouped by the length of their sound keys, so a word is only compared with words of a similar length
+#[derive(Default)]
+pub struct SoundsLikeIndex {
+    words: Vec<(Homograph, SoundKeys)>,
+    /// The indexes of the words with a pronunciation, by its length
+    by_pronunciation_length: BTreeMap<usize, Vec<usize>>,
+    /// The indexes of all the words, by the length of their spelling key
+    by_spelling_length: BTreeMap<usize, Vec<usize>>,
+}
+
+impl FromIterator<Homograph> for SoundsLikeIndex {
+    fn from_iter<T: IntoIterator<Item = Homograph>>(iter: T) -> Self {
+        let mut index = Self::default();
+
+        for homograph in iter {
+            let keys = SoundKeys::for_homographs([&homograph]);
+            let i = index.words.len();
+            if let Some(pronunciation) = &keys.pronunciation {
+                index
+                    .by_pronunciation_length
+                    .entry(pronunciation.chars().count())
+                    .or_default()
+                    .push(i);
+            }
+            index
+                .by_spelling_length
+                .entry(keys.spelling.chars().count())
+                .or_default()
+                .push(i);
+            index.words.push((homograph, keys));
+        }
+
+        index
+    }
+}
+
+impl SoundsLikeIndex {
+    /// The words which sound like the keys, with the number of sounds which differ, in the order they were added
+    pub fn sounds_like(&self, keys: &SoundKeys) -> Vec<(usize, &Homograph)> {
+        //Keys can only sound alike if their lengths differ by at most a quarter of the shorter one, or one
+        let near = |map: &BTreeMap<usize, Vec<usize>>, key: &str| {
+            let len = key.chars().count();
+            let tolerance = (len / 4).max(1);
+            map.range(len.saturating_sub(tolerance)..=len + tolerance)
+                .flat_map(|(_, indexes)| indexes.iter().copied())
+                .collect_vec()
+        };
+
+        let indexes = match &keys.pronunciation {
+            Some(pronunciation) => near(&self.by_pronunciation_length, pronunciation)
+                .into_iter()
+                .chain(
+                    near(&self.by_spelling_length, &keys.spelling)
+                        .into_iter()
+                        .filter(|i| self.words[*i].1.pronunciation.is_none()),
+                )
+                .collect_vec(),
+            None => near(&self.by_spelling_length, &keys.spelling),
+        };
+
+        indexes
+            .into_iter()
+            .sorted_unstable()
+            .filter_map(|i| {
+                let (homograph, other) = &self.words[i];
+                keys.sounds_like(other).map(|d| (d, homograph))
+            })
+            .collect()
+    }
+}
+
+/// The sounds of a pronunciation without stress and length marks
+pub fn pronunciation_sounds(ipa: &str) -> String {
+    ipa.chars()
+        .filter(|c| !IPA_IGNORED.contains(*c) && *c != 'ː' && *c != IPA_SYLLABIC)
+        .collect()
+}
+
+/// A rough spelling of how each word sounds, with letters that sound alike spelled the same way
+pub fn spelling_sounds(text: &str) -> String {
+    text.split(|c: char| !c.is_ascii_alphabetic())
+        .filter(|word| !word.is_empty())
+        .flat_map(|word| {
+            let chars = word.to_ascii_lowercase().chars().collect_vec();
+            let mut sounds = String::new();
+            let mut i = 0;
+
+            while i < chars.len() {
+                let next = chars.get(i + 1).copied();
+                match (chars[i], next) {
+                    ('p', Some('h')) => {
+                        sounds.push('f');
+                        i += 1;
+                    }
+                    ('c', Some('k')) | ('q', _) => sounds.push('k'),
+                    ('c', Some('e' | 'i' | 'y')) | ('z', _) => sounds.push('s'),
+                    ('c', _) => sounds.push('k'),
+                    ('x', _) => sounds.push_str("ks"),
+                    (c, _) => sounds.push(c),
+                }
+                i += 1;
+            }
+
+            let silent_e = match sounds.as_bytes() {
+                [_, .., b, b'e'] => !SPELLING_VOWELS.contains(*b as char),
+                _ => false,
+            };
+            if silent_e {
+                sounds.pop();
+            }
+
+            sounds.chars().collect_vec()
+        })
+        .dedup()
+        .collect()
+}
+
+/// Words which rhyme with a given word, other than the word itself
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Rhyme {
+    pub word: String,
+    pub keys: RhymeKeys,
+    /// The lower case text of the rhyming words, once looked up in the dictionary
+    pub rhymes: BTreeSet<String>,
+}
+
+impl Rhyme {
+    /// The name used to ask for rhymes in queries
+    pub const NAME: &'static str = "rhymes";
+
+    pub fn new(word: String) -> Self {
+        let keys = RhymeKeys {
+            pronunciations: Default::default(),
+            spelling: spelling_rhyme(&word),
+        };
+        Self {
+            word,
+            keys,
+            rhymes: Default::default(),
+        }
+    }
+
+    /// Find the rhyming words in the dictionary, using the pronunciation of the word if it has one
+    pub fn upgrade(&mut self, dict: &WordContext) {
+        if let Some(homograph) = dict.term_dict.try_find(&self.word) {
+            self.keys = RhymeKeys::for_homograph(&homograph);
+        }
+
+        self.rhymes = dict
+            .term_dict
+            .homographs
+            .iter()
+            .filter(|h| self.keys.rhymes_with(&RhymeKeys::for_homograph(h)))
+            .map(|h| h.text.to_ascii_lowercase())
+            .collect();
+        self.rhymes.remove(&self.word.to_ascii_lowercase());
+    }
+
+    pub fn allow(&self, term: &Homograph) -> bool {
+        self.rhymes.contains(&term.text.to_ascii_lowercase())
+    }
+}
+
+impl Display for Rhyme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}({})", Self::NAME, self.word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::prelude::*;
+    use ntest::test_case;
+
+    const WORDS: &str = "n\tmoon\t\t\tˈmuːn\nn\tspoon\t\t\tspuːn\nn\tgone\t\t\tɡɒn\nn\ttone\t\t\ttəʊn\nn\tboon\t\t\t\nn\tdune\t\t\tdjuːn dʒuːn\nn\torange\t\t\tˈɒrɪndʒ\nj\tblue\t\t\tbluː\na\tfast\t\t\tfɑːst\nv\tlaugh\t\t\tlɑːf\n";
+
+    #[test_case("ˈɒrɪndʒ", "ɒrɪndʒ", name = "orange")]
+    #[test_case("muːn", "uːn", name = "no_stress_mark")]
+    #[test_case("ɪnˈdʒɔɪ", "ɔɪ", name = "final_stress")]
+    #[test_case("ˈkætəˌpʌlt", "ætəpʌlt", name = "secondary_stress")]
+    fn test_pronunciation_rhyme(ipa: &str, expected: &str) {
+        assert_eq!(pronunciation_rhyme(ipa).unwrap(), expected);
+    }
+
+    #[test_case("ˈkætəˌpʌlt", 3, name = "catapult")]
+    #[test_case("ˈbʌtn̩", 2, name = "syllabic_consonant")]
+    #[test_case("ˈhaɪkuː", 2, name = "diphthong")]
+    fn test_pronunciation_syllables(ipa: &str, expected: usize) {
+        assert_eq!(pronunciation_syllables(ipa), expected);
+    }
+
+    #[test_case("cat", 1, name = "one")]
+    #[test_case("tone", 1, name = "silent_e_syllable")]
+    #[test_case("table", 2, name = "le_ending")]
+    #[test_case("the", 1, name = "short_e")]
+    #[test_case("ice cream", 2, name = "two_words")]
+    #[test_case("banana", 3, name = "three")]
+    fn test_spelling_syllables(text: &str, expected: usize) {
+        assert_eq!(spelling_syllables(text), expected);
+    }
+
+    #[test_case("lettuce", "letus", name = "soft_c_and_silent_e")]
+    #[test_case("let us", "letus", name = "across_words")]
+    #[test_case("phone", "fon", name = "ph")]
+    #[test_case("quick box", "kuikboks", name = "hard_sounds")]
+    fn test_spelling_sounds(text: &str, expected: &str) {
+        assert_eq!(spelling_sounds(text), expected);
+    }
+
+    #[test_case("moon", "oon", name = "vowel_group")]
+    #[test_case("tone", "one", name = "silent_e")]
+    #[test_case("free", "ee", name = "double_e")]
+    #[test_case("Cat", "at", name = "upper_case")]
+    fn test_spelling_rhyme(text: &str, expected: &str) {
+        assert_eq!(spelling_rhyme(text).unwrap(), expected);
+    }
+
+    #[test_case("#rhymes(moon)", "spoon boon dune", name = "rhymes")]
+    #[test_case("#rhymes(tone)", "", name = "spelling_is_not_sound")]
+    #[test_case("#rhymes(bone)", "gone tone", name = "unknown_word_uses_spelling")]
+    #[test_case("4 + #rhymes(moon)", "boon dune", name = "fixed_length")]
+    #[test_case("snoop =a #rhymes(moon)", "snoop : spoon", name = "anagram")]
+    fn test_rhymes(input: &str, expected: &str) {
+        let dict = WordContext::from(TermDict::from_csv(WORDS).unwrap());
+        let mut question = question_parse(input).unwrap();
+        question.upgrade_literals(&dict);
+
+        let actual = question.solve(&dict).map(|s| s.get_text()).join(" ");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test_case("syl:2", "orange", name = "two")]
+    #[test_case("syl:1 + #rhymes(moon)", "spoon boon dune", name = "one_and_rhyme")]
+    #[test_case("syl:2..3 / gone", "gone orange", name = "range")]
+    #[test_case("!phrase(syl:2) + moon", "the moon blue moon a moon", name = "phrase")]
+    #[test_case(
+        "!phrase(syl:3) + orange + -the",
+        "blue orange an orange",
+        name = "phrase_without_the"
+    )]
+    fn test_syllables(input: &str, expected: &str) {
+        let dict = WordContext::from(TermDict::from_csv(WORDS).unwrap());
+        let mut question = question_parse(input).unwrap();
+        question.upgrade_literals(&dict);
+
+        let actual = question.solve(&dict).map(|s| s.get_text()).join(" ");
+
+        assert_eq!(actual, expected);
+    }
+}
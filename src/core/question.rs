@@ -1,10 +1,23 @@
+use std::fmt::Display;
+
 use crate::core::prelude::*;
 use auto_enums::auto_enum;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum Question {
     Expression(Expression),
-    Equation(Equation),
+    Equation(Box<Equation>),
+    Ladder(WordLadder),
+}
+
+impl Display for Question {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Question::Expression(ex) => write!(f, "{ex}"),
+            Question::Equation(eq) => write!(f, "{eq}"),
+            Question::Ladder(ladder) => write!(f, "{ladder}"),
+        }
+    }
 }
 
 impl Question {
@@ -17,13 +30,20 @@ impl Question {
             Question::Expression(ex) => ex.solve(dict).map(QuestionSolution::Expression),
 
             Question::Equation(eq) => eq.solve(dict),
+            Question::Ladder(ladder) => ladder.solve(dict).map(QuestionSolution::Ladder),
         }
     }
 
+    /// Whether this question has no terms at all
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Question::Expression(Expression::FixedLength(fle)) if fle.words.is_empty())
+    }
+
     pub fn is_too_difficult(&self, dict: &WordContext) -> bool {
         match self {
             Question::Expression(_) => false,
             Question::Equation(eq) => eq.is_too_difficult(dict),
+            Question::Ladder(_) => false,
         }
     }
 
@@ -32,6 +52,7 @@ impl Question {
         match self {
             Question::Expression(ex) => ex.upgrade_literals(dict),
             Question::Equation(eq) => eq.upgrade_literals(dict),
+            Question::Ladder(ladder) => ladder.upgrade_literals(dict),
         }
     }
 }
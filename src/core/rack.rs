@@ -0,0 +1,66 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::core::prelude::*;
+
+/// Letters to use as an anagram source, where each `?` is a blank which can stand for any letter
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Rack {
+    pub key: AnagramKey,
+    /// The rack as written, used as the letters of an equation
+    pub homograph: Homograph,
+}
+
+impl FromStr for Rack {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            key: AnagramKey::from_str(s)?,
+            homograph: Homograph {
+                text: s.to_string().into(),
+                is_single_word: true,
+                meanings: Default::default(),
+            },
+        })
+    }
+}
+
+impl Rack {
+    /// Words which use every letter and blank of the rack
+    pub fn allow(&self, term: &Homograph) -> bool {
+        AnagramKey::from_str(&term.text)
+            .is_ok_and(|key| key.len() == self.key.len() && self.key.try_sub(key).is_some())
+    }
+}
+
+impl Display for Rack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}]", self.homograph.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::core::prelude::*;
+    use crate::language::prelude::*;
+    use ntest::test_case;
+
+    const WORDS: &str = "n\tact\t\t\nn\tcat\t\t\nn\tcart\t\t\nn\tice\t\t\nn\trice\t\t\n";
+
+    #[test_case("[ca?]", "act cat", name = "solves_to_words")]
+    #[test_case("[ca??] [ic?]", "cart ice", name = "solves_within_phrase")]
+    #[test_case("[ca?] + 3", "act cat", name = "filter_with_blank")]
+    #[test_case("[ca??] + *", "cart", name = "filter_with_two_blanks")]
+    #[test_case("[ice?] =a *", "ice? : rice (? = r)", name = "anagram_source")]
+    fn test_rack(input: &str, expected: &str) {
+        let dict = WordContext::from(TermDict::from_csv(WORDS).unwrap());
+        let mut question = question_parse(input).unwrap();
+        question.upgrade_literals(&dict);
+
+        let actual = question.solve(&dict).map(|s| s.get_text()).join(" ");
+
+        assert_eq!(actual, expected);
+    }
+}
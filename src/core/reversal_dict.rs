@@ -0,0 +1,180 @@
+use std::{collections::BTreeMap, ops::Bound};
+
+use itertools::Itertools;
+use smallvec::SmallVec;
+
+use crate::core::prelude::*;
+
+/// Words indexed by their letters in reverse order, ignoring spaces and punctuation
+pub struct ReversalDict {
+    pub words: BTreeMap<String, SmallVec<[Homograph; 1]>>,
+    /// The same words indexed by their letters in the usual order
+    pub forwards: BTreeMap<String, SmallVec<[Homograph; 1]>>,
+    max_length: usize,
+}
+
+impl From<&TermDict> for ReversalDict {
+    fn from(term_dict: &TermDict) -> Self {
+        Self::from(term_dict.homographs.iter().cloned())
+    }
+}
+
+impl<T: Iterator<Item = Homograph>> From<T> for ReversalDict {
+    fn from(iter: T) -> Self {
+        let mut words: BTreeMap<String, SmallVec<[Homograph; 1]>> = BTreeMap::new();
+        let mut forwards: BTreeMap<String, SmallVec<[Homograph; 1]>> = BTreeMap::new();
+
+        for homograph in iter.sorted().dedup() {
+            let key = Self::letters(&homograph.text).collect::<String>();
+            if !key.is_empty() {
+                words
+                    .entry(key.chars().rev().collect())
+                    .or_default()
+                    .push(homograph.clone());
+                forwards.entry(key).or_default().push(homograph);
+            }
+        }
+
+        let max_length = words.keys().map(|k| k.len()).max().unwrap_or_default();
+
+        ReversalDict {
+            words,
+            forwards,
+            max_length,
+        }
+    }
+}
+
+impl ReversalDict {
+    /// The lower case letters of the text, ignoring everything else
+    pub fn letters(text: &str) -> impl DoubleEndedIterator<Item = char> + '_ {
+        text.chars()
+            .filter(|c| c.is_ascii_alphabetic())
+            .map(|c| c.to_ascii_lowercase())
+    }
+
+    /// Find every sequence of words which spells the text backwards.
+    /// Each word of the result is a word whose reversal is a piece of the text, so the pieces are found in order and then reversed.
+    pub fn solve_for_word(
+        &self,
+        word: &str,
+        settings: AnagramSettings,
+    ) -> impl '_ + Iterator<Item = ExpressionSolution> {
+        self.solve_with_index(&self.words, word, settings, true)
+    }
+
+    /// Find every sequence of words which spells the text, ignoring spaces
+    pub fn solve_for_text(
+        &self,
+        text: &str,
+        settings: AnagramSettings,
+    ) -> impl '_ + Iterator<Item = ExpressionSolution> {
+        self.solve_with_index(&self.forwards, text, settings, false)
+    }
+
+    fn solve_with_index<'a>(
+        &'a self,
+        index: &'a BTreeMap<String, SmallVec<[Homograph; 1]>>,
+        text: &str,
+        settings: AnagramSettings,
+        reverse: bool,
+    ) -> impl 'a + Iterator<Item = ExpressionSolution> {
+        let letters = Self::letters(text).collect::<String>();
+        let mut pieces = vec![];
+        let mut results = vec![];
+        self.split(index, &letters, settings, &mut pieces, &mut results);
+
+        results.into_iter().flat_map(move |mut pieces| {
+            if reverse {
+                pieces.reverse();
+            }
+            pieces
+                .into_iter()
+                .map(|homographs| homographs.iter().cloned())
+                .multi_cartesian_product()
+                .map(|x| ExpressionSolution {
+                    homographs: SmallVec::from_vec(x),
+                })
+        })
+    }
+
+    /// Entries of the index whose keys are a prefix of the text or start with the text
+    pub fn compatible<'a: 't, 't>(
+        &self,
+        index: &'a BTreeMap<String, SmallVec<[Homograph; 1]>>,
+        text: &'t str,
+    ) -> impl Iterator<Item = (&'a String, &'a SmallVec<[Homograph; 1]>)> + 't {
+        let prefixes = (1..=self.max_length.min(text.len()))
+            .filter_map(move |length| index.get_key_value(&text[..length]));
+        let extensions = index
+            .range::<str, _>((Bound::Excluded(text), Bound::Unbounded))
+            .take_while(move |(key, _)| key.starts_with(text));
+
+        prefixes.chain(extensions)
+    }
+
+    fn split<'a>(
+        &self,
+        index: &'a BTreeMap<String, SmallVec<[Homograph; 1]>>,
+        remaining: &str,
+        settings: AnagramSettings,
+        pieces: &mut Vec<&'a SmallVec<[Homograph; 1]>>,
+        results: &mut Vec<Vec<&'a SmallVec<[Homograph; 1]>>>,
+    ) {
+        if remaining.is_empty() {
+            if !pieces.is_empty() {
+                results.push(pieces.clone());
+            }
+            return;
+        }
+        if settings.max_words.is_some_and(|max| pieces.len() >= max) {
+            return;
+        }
+
+        let min = (settings.min_word_length as usize).max(1);
+        for length in min..=self.max_length.min(remaining.len()) {
+            if let Some(homographs) = index.get(&remaining[..length]) {
+                pieces.push(homographs);
+                self.split(index, &remaining[length..], settings, pieces, results);
+                pieces.pop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::core::prelude::*;
+    use ntest::test_case;
+
+    #[test_case("stressed", "desserts", 1, name = "one_word")]
+    #[test_case("live", "evil", 1, name = "live_evil")]
+    #[test_case("stop a tub", "but a pots; but apots", 3, name = "several_words")]
+    #[test_case("stop a tub", "", 1, name = "too_many_words")]
+    #[test_case("dog", "", 3, name = "no_reversal")]
+    fn test_solve(input: &str, expected: &str, max_words: usize) {
+        let words = "desserts evil but a pots apots"
+            .split_ascii_whitespace()
+            .map(|text| Homograph {
+                text: text.into(),
+                is_single_word: true,
+                meanings: Default::default(),
+            });
+        let dict = ReversalDict::from(words);
+
+        let actual = dict
+            .solve_for_word(
+                input,
+                AnagramSettings {
+                    min_word_length: 1,
+                    max_words: Some(max_words),
+                },
+            )
+            .map(|s| s.get_text())
+            .join("; ");
+
+        assert_eq!(actual, expected);
+    }
+}
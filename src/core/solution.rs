@@ -1,3 +1,5 @@
+use std::{ops::Range, str::FromStr};
+
 use crate::core::prelude::*;
 
 use itertools::Itertools;
@@ -10,6 +12,13 @@ pub enum QuestionSolution {
     Expression(ExpressionSolution),
     Anagram(AnagramSolution),
     Spoonerism(SpoonerismSolution),
+    SubAnagram(SubAnagramSolution),
+    Reversal(ReversalSolution),
+    Hidden(HiddenSolution),
+    Ladder(LadderSolution),
+    Edit(EditSolution),
+    Gematria(GematriaSolution),
+    Pun(PunSolution),
 }
 
 impl QuestionSolution {
@@ -18,6 +27,13 @@ impl QuestionSolution {
             QuestionSolution::Expression(e) => e.get_text(),
             QuestionSolution::Anagram(a) => a.get_text(),
             QuestionSolution::Spoonerism(a) => a.get_text(),
+            QuestionSolution::SubAnagram(a) => a.get_text(),
+            QuestionSolution::Reversal(a) => a.get_text(),
+            QuestionSolution::Hidden(a) => a.get_text(),
+            QuestionSolution::Ladder(a) => a.get_text(),
+            QuestionSolution::Edit(a) => a.get_text(),
+            QuestionSolution::Gematria(a) => a.get_text(),
+            QuestionSolution::Pun(a) => a.get_text(),
         }
     }
 }
@@ -32,14 +48,56 @@ impl ExpressionSolution {
         self.homographs.iter().map(|x| x.text.clone()).join(" ")
     }
 
+    /// The text with each word that has an emoji replaced by its emoji
+    pub fn get_rebus(&self) -> String {
+        self.homographs
+            .iter()
+            .map(|x| x.emoji().unwrap_or(x.text.as_ref()))
+            .join(" ")
+    }
+
     pub fn contains_word(&self, word: &Homograph) -> bool {
         self.homographs.iter().any(|x| x.text == word.text)
     }
 }
 
 impl AnagramSolution {
+    /// `blanks` is the number of blanks used on either side, so they are only looked for when there are some
+    pub fn new(left: ExpressionSolution, right: ExpressionSolution, blanks: usize) -> Self {
+        let blanks = if blanks == 0 {
+            AnagramKey::empty()
+        } else {
+            Self::find_blanks(&left, &right)
+        };
+        Self {
+            left,
+            right,
+            blanks,
+        }
+    }
+
+    /// The letters which blanks on one side stand for on the other side
+    fn find_blanks(left: &ExpressionSolution, right: &ExpressionSolution) -> AnagramKey {
+        let (Ok(left), Ok(right)) = (
+            AnagramKey::from_str(left.get_text().as_str()),
+            AnagramKey::from_str(right.get_text().as_str()),
+        ) else {
+            return AnagramKey::empty();
+        };
+
+        let (rack, word) = match (left.blanks(), right.blanks()) {
+            (0, 0) => return AnagramKey::empty(),
+            (_, 0) => (left, right),
+            _ => (right, left),
+        };
+
+        word.try_sub(rack.without_blanks())
+            .unwrap_or_else(AnagramKey::empty)
+    }
+
     pub fn get_text(&self) -> String {
-        self.left
+        let text = self
+            .left
             .homographs
             .iter()
             .map(|x| x.text.clone())
@@ -51,7 +109,13 @@ impl AnagramSolution {
                 .iter()
                 .map(|x| x.text.clone())
                 .join(" ")
-                .as_str()
+                .as_str();
+
+        if self.blanks.is_empty() {
+            text
+        } else {
+            format!("{text} (? = {})", self.blanks.letters())
+        }
     }
 }
 
@@ -59,6 +123,8 @@ impl AnagramSolution {
 pub struct AnagramSolution {
     pub left: ExpressionSolution,
     pub right: ExpressionSolution,
+    /// The letters used in place of blanks
+    pub blanks: AnagramKey,
 }
 #[derive(Clone, Serialize, PartialEq, Eq)]
 pub struct SpoonerismSolution {
@@ -112,6 +178,7 @@ impl AnagramSolution {
         AnagramSolution {
             left: self.right,
             right: self.left,
+            blanks: self.blanks,
         }
     }
 
@@ -131,3 +198,234 @@ impl AnagramSolution {
                 .all(|(x, y)| x.text == y.text)
     }
 }
+
+/// Words made from some of the letters of the left side, along with the letters left over
+#[derive(Clone, Serialize, PartialEq, Eq)]
+pub struct SubAnagramSolution {
+    pub left: ExpressionSolution,
+    pub right: ExpressionSolution,
+    pub remainder: AnagramKey,
+}
+
+impl SubAnagramSolution {
+    pub fn get_text(&self) -> String {
+        let text = self.left.get_text() + " : " + self.right.get_text().as_str();
+
+        if self.remainder.is_empty() {
+            text
+        } else {
+            format!("{text} + {}", self.remainder.letters())
+        }
+    }
+
+    /// Whether the right side is just the words of the left side
+    pub fn is_trivial(&self) -> bool {
+        self.remainder.is_empty()
+            && self
+                .left
+                .homographs
+                .iter()
+                .map(|x| x.text.to_ascii_lowercase())
+                .sorted()
+                .eq(self
+                    .right
+                    .homographs
+                    .iter()
+                    .map(|x| x.text.to_ascii_lowercase())
+                    .sorted())
+    }
+
+    /// The number of letters of the left side used by the right side
+    pub fn letters_used(&self) -> usize {
+        self.right
+            .homographs
+            .iter()
+            .map(|h| h.text.chars().filter(|c| c.is_alphabetic()).count())
+            .sum()
+    }
+}
+
+/// Solutions are ordered by the number of letters used
+impl Ord for SubAnagramSolution {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.letters_used()
+            .cmp(&other.letters_used())
+            .then_with(|| self.get_text().cmp(&other.get_text()))
+    }
+}
+
+impl PartialOrd for SubAnagramSolution {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Words which spell the left side backwards
+#[derive(Clone, Serialize, PartialEq, Eq)]
+pub struct ReversalSolution {
+    pub left: ExpressionSolution,
+    pub right: ExpressionSolution,
+}
+
+impl ReversalSolution {
+    pub fn get_text(&self) -> String {
+        self.left.get_text() + " : " + self.right.get_text().as_str()
+    }
+
+    pub fn flip(self) -> Self {
+        Self {
+            left: self.right,
+            right: self.left,
+        }
+    }
+
+    /// Whether the right side is the same words as the left side, as happens with palindromes
+    pub fn is_trivial(&self) -> bool {
+        self.left
+            .homographs
+            .iter()
+            .map(|x| x.text.to_ascii_lowercase())
+            .eq(self
+                .right
+                .homographs
+                .iter()
+                .map(|x| x.text.to_ascii_lowercase()))
+    }
+}
+
+/// Words hidden in the letters of the left side, possibly backwards
+#[derive(Clone, Serialize, PartialEq, Eq)]
+pub struct HiddenSolution {
+    pub left: ExpressionSolution,
+    pub right: ExpressionSolution,
+    pub reversed: bool,
+}
+
+impl HiddenSolution {
+    pub fn get_text(&self) -> String {
+        let text = self.left.get_text() + " : " + self.right.get_text().as_str();
+
+        if self.reversed {
+            format!("{text} (reversed)")
+        } else {
+            text
+        }
+    }
+
+    /// Whether the right side is just one of the words of the left side
+    pub fn is_trivial(&self) -> bool {
+        !self.reversed
+            && self.right.homographs.len() == 1
+            && self.left.homographs.iter().any(|x| {
+                x.text
+                    .eq_ignore_ascii_case(self.right.homographs[0].text.as_ref())
+            })
+    }
+}
+
+/// A chain of words where each differs from the last by one letter
+#[derive(Clone, Serialize, PartialEq, Eq)]
+pub struct LadderSolution {
+    pub steps: ExpressionSolution,
+}
+
+impl LadderSolution {
+    pub fn get_text(&self) -> String {
+        self.steps
+            .homographs
+            .iter()
+            .map(|x| x.text.clone())
+            .join(" → ")
+    }
+}
+
+/// An anagram of the left side after some letters were added or removed
+#[derive(Clone, Serialize, PartialEq, Eq)]
+pub struct EditSolution {
+    pub left: ExpressionSolution,
+    pub right: ExpressionSolution,
+    pub added: AnagramKey,
+    pub removed: AnagramKey,
+}
+
+impl EditSolution {
+    /// The letters removed and added, such as `-a +s`
+    pub fn describe_edit(&self) -> String {
+        let removed = (!self.removed.is_empty()).then(|| format!("-{}", self.removed.letters()));
+        let added = (!self.added.is_empty()).then(|| format!("+{}", self.added.letters()));
+
+        removed.into_iter().chain(added).join(" ")
+    }
+
+    pub fn get_text(&self) -> String {
+        format!(
+            "{} : {} ({})",
+            self.left.get_text(),
+            self.right.get_text(),
+            self.describe_edit()
+        )
+    }
+}
+
+/// Words whose letters add up to the same value as the left side
+#[derive(Clone, Serialize, PartialEq, Eq)]
+pub struct GematriaSolution {
+    pub left: ExpressionSolution,
+    pub right: ExpressionSolution,
+    pub value: u32,
+}
+
+impl GematriaSolution {
+    pub fn get_text(&self) -> String {
+        format!(
+            "{} : {} ({})",
+            self.left.get_text(),
+            self.right.get_text(),
+            self.value
+        )
+    }
+
+    /// Whether the right side is the same words as the left side
+    pub fn is_trivial(&self) -> bool {
+        self.left
+            .get_text()
+            .eq_ignore_ascii_case(&self.right.get_text())
+    }
+}
+
+/// A phrase with some of its words replaced by a word which sounds similar
+#[derive(Clone, Serialize, PartialEq, Eq)]
+pub struct PunSolution {
+    pub left: ExpressionSolution,
+    pub right: ExpressionSolution,
+    /// The positions of the words of the left side which were replaced
+    pub replaced: Range<usize>,
+}
+
+impl PunSolution {
+    pub fn get_text(&self) -> String {
+        format!(
+            "{} : {} ({})",
+            self.left.get_text(),
+            self.right.get_text(),
+            self.describe_swap()
+        )
+    }
+
+    /// The word which replaced the words of the left side
+    pub fn replacement(&self) -> &Homograph {
+        &self.right.homographs[self.replaced.start]
+    }
+
+    /// The words which were replaced and what they were replaced with
+    pub fn describe_swap(&self) -> String {
+        format!(
+            "{} → {}",
+            self.left.homographs[self.replaced.clone()]
+                .iter()
+                .map(|h| h.text.clone())
+                .join(" "),
+            self.replacement().text
+        )
+    }
+}
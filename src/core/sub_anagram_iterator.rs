@@ -0,0 +1,74 @@
+use smallvec::SmallVec;
+use std::ops::Bound;
+
+use crate::core::prelude::*;
+
+/// Finds every combination of words whose letters are contained in a key.
+/// Each item is the words used along with the letters left over.
+pub struct SubAnagramIterator<'b, const N: usize> {
+    dict: &'b AnagramDict,
+    stack: SmallVec<[(AnagramKey, Bound<AnagramKey>); N]>,
+    used_words: SmallVec<[AnagramKey; N]>,
+    settings: AnagramSettings,
+}
+
+impl<'b, const N: usize> SubAnagramIterator<'b, N> {
+    pub fn create(dict: &'b AnagramDict, key: AnagramKey, settings: AnagramSettings) -> Self {
+        let mut stack = SmallVec::<[(AnagramKey, Bound<AnagramKey>); N]>::new();
+        //Words using blanks can come after the key
+        let bound = if key.blanks() > 0 {
+            Bound::Unbounded
+        } else {
+            Bound::Included(key)
+        };
+        stack.push((key, bound));
+
+        Self {
+            dict,
+            stack,
+            settings,
+            used_words: Default::default(),
+        }
+    }
+}
+
+impl<'b, const N: usize> Iterator for SubAnagramIterator<'b, N> {
+    type Item = (SmallVec<[AnagramKey; N]>, AnagramKey);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(top) = self.stack.last_mut() {
+            if let Some((remainder, next_key)) = self
+                .dict
+                .words
+                .range((Bound::Unbounded, top.1))
+                .rev()
+                .filter(|(&next_key, _)| self.settings.allow_key(&next_key))
+                .find_map(|(&next_key, _)| {
+                    (top.0.try_sub(next_key)).map(|remainder| (remainder, next_key))
+                })
+            {
+                top.1 = Bound::Excluded(next_key);
+
+                let mut new_used = self.used_words.clone();
+                new_used.push(next_key);
+
+                let can_add_word = self
+                    .settings
+                    .max_words
+                    .is_none_or(|max| new_used.len() < max);
+
+                if can_add_word && self.settings.allow_key(&remainder) {
+                    self.used_words.push(next_key);
+                    self.stack.push((remainder, Bound::Included(next_key)));
+                }
+
+                return Some((new_used, remainder));
+            } else {
+                self.stack.pop();
+                self.used_words.pop();
+            }
+        }
+
+        None
+    }
+}
@@ -0,0 +1,117 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Display,
+};
+
+use itertools::Itertools;
+
+use crate::core::prelude::*;
+
+/// The links from each thesaurus id to the more specific ids below it
+#[derive(Debug, Default)]
+pub struct Taxonomy {
+    hyponyms: HashMap<&'static str, Vec<&'static str>>,
+}
+
+impl From<&TermDict> for Taxonomy {
+    fn from(term_dict: &TermDict) -> Self {
+        let mut hyponyms: HashMap<&'static str, Vec<&'static str>> = HashMap::new();
+
+        for (synset, hypernym) in term_dict.homographs.iter().flat_map(|h| h.hypernyms()) {
+            hyponyms.entry(hypernym).or_default().push(synset);
+        }
+        for children in hyponyms.values_mut() {
+            children.sort_unstable();
+            children.dedup();
+        }
+
+        Self { hyponyms }
+    }
+}
+
+impl Taxonomy {
+    /// Every id below any of the given ids, not including those ids unless they are below another
+    pub fn descendants<'a>(
+        &self,
+        synsets: impl Iterator<Item = &'a str>,
+    ) -> BTreeSet<&'static str> {
+        let mut result = BTreeSet::new();
+        let mut stack = synsets
+            .filter_map(|s| self.hyponyms.get(s))
+            .flatten()
+            .copied()
+            .collect_vec();
+
+        while let Some(synset) = stack.pop() {
+            if result.insert(synset) {
+                stack.extend(self.hyponyms.get(synset).into_iter().flatten());
+            }
+        }
+
+        result
+    }
+}
+
+/// Words which are a kind of the given word, such as the animals for `animal`
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Category {
+    pub word: String,
+    /// The ids of every kind of the word, once looked up in the taxonomy
+    pub synsets: BTreeSet<&'static str>,
+}
+
+impl Category {
+    /// The name used to ask for a category in queries
+    pub const NAME: &'static str = "is";
+
+    pub fn new(word: String) -> Self {
+        Self {
+            word,
+            synsets: Default::default(),
+        }
+    }
+
+    /// Look up every kind of the word in the taxonomy
+    pub fn upgrade(&mut self, dict: &WordContext) {
+        if let Some(homograph) = dict.term_dict.try_find(&self.word) {
+            self.synsets = dict.taxonomy.descendants(homograph.synsets());
+        }
+    }
+
+    pub fn allow(&self, term: &Homograph) -> bool {
+        term.synsets().any(|s| self.synsets.contains(s))
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}({})", Self::NAME, self.word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::core::prelude::*;
+    use crate::language::prelude::*;
+    use ntest::test_case;
+
+    const WORDS: &str = "n\tanimal\t\t\t\t\t01\t\t\t\nn\tdog\t\t\t\t\t02 03\t\t\t02:04 03:05\nn\tcanine\t\t\t\t\t04\t\t\t04:01\nn\tcat\t\t\t\t\t06\t\t\t06:01\nn\tcad\t\t\t\t\t03\t\t\t03:05\nn\tperson\t\t\t\t\t05\t\t\t\nn\ttool\t\t\t\t\t07\t\t\t\nn\thammer\t\t\t\t\t08\t\t\t08:07\n";
+
+    #[test_case("#is(animal)", "dog canine cat", name = "ancestor")]
+    #[test_case("#is(canine)", "dog", name = "parent")]
+    #[test_case("#is(person)", "dog cad", name = "second_sense")]
+    #[test_case("#is(tool) + h*", "hammer", name = "with_pattern")]
+    #[test_case("#is(unicorn)", "", name = "unknown_word")]
+    #[test_case("cta =a #is(animal)", "cta : cat", name = "anagram_of_category")]
+    fn test_category(input: &str, expected: &str) {
+        let dict = WordContext::from(TermDict::from_csv(WORDS).unwrap());
+        let mut question = question_parse(input).unwrap();
+        question.upgrade_literals(&dict);
+
+        let actual = question.solve(&dict).map(|s| s.get_text()).join(" ");
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -54,6 +54,13 @@ impl TermDict {
                 }
             }
 
+            let pronunciation = parts.next().filter(|x| !x.is_empty());
+            let emoji = parts.next().filter(|x| !x.is_empty());
+            let synsets = parts.next().filter(|x| !x.is_empty());
+            let antonyms = parts.next().filter(|x| !x.is_empty());
+            let similar = parts.next().filter(|x| !x.is_empty());
+            let hypernyms = parts.next().filter(|x| !x.is_empty());
+
             let part_of_speech = PartOfSpeech::from_str(pos_lit)?;
             let term = (
                 text,
@@ -61,6 +68,12 @@ impl TermDict {
                     part_of_speech,
                     tags,
                     definition,
+                    pronunciation,
+                    emoji,
+                    synsets,
+                    antonyms,
+                    similar,
+                    hypernyms,
                 },
             );
             terms.push(term);
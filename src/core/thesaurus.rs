@@ -0,0 +1,132 @@
+use std::{collections::BTreeSet, fmt::Display, str::FromStr};
+
+use crate::core::prelude::*;
+
+/// How words are related in the thesaurus
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Relation {
+    /// Words which share a meaning
+    Synonym,
+    /// Words with the opposite meaning
+    Antonym,
+    /// Words with a similar meaning
+    Similar,
+}
+
+impl Relation {
+    /// The names that can be used to refer to relations in queries
+    pub const NAMES: [(&'static str, Relation); 3] = [
+        ("syn", Relation::Synonym),
+        ("ant", Relation::Antonym),
+        ("sim", Relation::Similar),
+    ];
+
+    /// The name used to refer to this relation in queries
+    pub fn name(&self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|(_, relation)| relation == self)
+            .map(|(name, _)| *name)
+            .expect("Every relation has a name")
+    }
+}
+
+impl FromStr for Relation {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s))
+            .map(|(_, relation)| *relation)
+            .ok_or_else(|| anyhow::anyhow!("Could not parse {} as relation", s))
+    }
+}
+
+/// Words related to a given word in the thesaurus, other than the word itself
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RelatedWords {
+    pub relation: Relation,
+    pub word: String,
+    /// The lower case text of the related words, once looked up in the dictionary
+    pub related: BTreeSet<String>,
+}
+
+impl RelatedWords {
+    pub fn new(relation: Relation, word: String) -> Self {
+        Self {
+            relation,
+            word,
+            related: Default::default(),
+        }
+    }
+
+    /// Look up the related words in the dictionary
+    pub fn upgrade(&mut self, dict: &WordContext) {
+        let Some(homograph) = dict.term_dict.try_find(&self.word) else {
+            return;
+        };
+
+        self.related = match self.relation {
+            Relation::Synonym => {
+                let synsets = homograph.synsets().collect::<BTreeSet<_>>();
+                dict.term_dict
+                    .homographs
+                    .iter()
+                    .filter(|h| h.synsets().any(|s| synsets.contains(s)))
+                    .map(|h| h.text.to_ascii_lowercase())
+                    .collect()
+            }
+            Relation::Antonym => homograph
+                .antonyms()
+                .map(|w| w.to_ascii_lowercase())
+                .collect(),
+            Relation::Similar => homograph
+                .similar()
+                .map(|w| w.to_ascii_lowercase())
+                .collect(),
+        };
+        self.related.remove(&self.word.to_ascii_lowercase());
+    }
+
+    pub fn allow(&self, term: &Homograph) -> bool {
+        self.related.contains(&term.text.to_ascii_lowercase())
+    }
+}
+
+impl Display for RelatedWords {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}({})", self.relation.name(), self.word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::core::prelude::*;
+    use crate::language::prelude::*;
+    use ntest::test_case;
+
+    const WORDS: &str = "j\thappy\t\t\t\t\t01 02\tunhappy\tblessed\nj\tglad\t\t\t\t\t01\t\t\nj\tfelicitous\t\t\t\t\t02\t\t\nj\tunhappy\t\t\t\t\t03\thappy\t\nj\tblessed\t\t\t\t\t04\t\t\nn\tdog\t\t\t\t\t\t\t\n";
+
+    #[test_case("#syn(happy)", "felicitous glad", name = "synonyms")]
+    #[test_case("#ant(happy)", "unhappy", name = "antonyms")]
+    #[test_case("#sim(happy)", "blessed", name = "similar")]
+    #[test_case("#syn(happy) + 4", "glad", name = "with_length")]
+    #[test_case("#syn(sad)", "", name = "unknown_word")]
+    #[test_case("dalg =a #syn(happy)", "dalg : glad", name = "anagram_of_synonym")]
+    fn test_related_words(input: &str, expected: &str) {
+        let dict = WordContext::from(TermDict::from_csv(WORDS).unwrap());
+        let mut question = question_parse(input).unwrap();
+        question.upgrade_literals(&dict);
+
+        let actual = question
+            .solve(&dict)
+            .map(|s| s.get_text())
+            .sorted()
+            .join(" ");
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -4,6 +4,9 @@ use crate::core::prelude::*;
 pub struct WordContext {
     pub term_dict: TermDict,
     pub anagram_dict: AnagramDict,
+    pub reversal_dict: ReversalDict,
+    pub definition_index: DefinitionIndex,
+    pub taxonomy: Taxonomy,
 }
 
 impl WordContext {
@@ -15,12 +18,26 @@ impl WordContext {
         debug!("Loading Word context");
         let now = instant::Instant::now();
         let term_dict = TermDict::from_term_data().unwrap();
-        let anagram_dict = AnagramDict::from(term_dict.homographs.clone().into_iter());
+        let context = WordContext::from(term_dict);
 
         debug!("Loaded word context in {:?}", now.elapsed());
+        context
+    }
+}
+
+impl From<TermDict> for WordContext {
+    fn from(term_dict: TermDict) -> Self {
+        let anagram_dict = AnagramDict::from(term_dict.homographs.clone().into_iter());
+        let reversal_dict = ReversalDict::from(&term_dict);
+        let definition_index = DefinitionIndex::from(&term_dict);
+        let taxonomy = Taxonomy::from(&term_dict);
+
         WordContext {
             term_dict,
             anagram_dict,
+            reversal_dict,
+            definition_index,
+            taxonomy,
             //phrase_expressions,
         }
     }
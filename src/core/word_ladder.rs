@@ -0,0 +1,212 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+};
+
+use itertools::Itertools;
+
+use crate::core::prelude::*;
+
+/// Chains of words from one word to another, changing one letter at a time
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct WordLadder {
+    pub start: String,
+    pub end: String,
+    /// A query which every step between the start and the end must satisfy
+    pub steps: Option<WordQuery>,
+}
+
+impl Display for WordLadder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ladder({}, {}", self.start, self.end)?;
+        if let Some(steps) = &self.steps {
+            write!(f, ", {steps}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl WordLadder {
+    pub fn upgrade_literals(&mut self, dict: &WordContext) {
+        if let Some(steps) = &mut self.steps {
+            steps.upgrade_literals(dict);
+        }
+    }
+
+    /// Every shortest chain from the start to the end
+    pub fn solve<'a>(&'a self, dict: &'a WordContext) -> impl Iterator<Item = LadderSolution> + 'a {
+        let start = self.start.to_ascii_lowercase();
+        let end = self.end.to_ascii_lowercase();
+
+        let chains = if start.len() == end.len() && start != end {
+            let words = self.candidates(dict, &start, &end);
+            let index = NeighbourIndex::new(&words);
+            index.shortest_chains(&start, &end)
+        } else {
+            vec![]
+        };
+
+        chains.into_iter().map(|chain| LadderSolution {
+            steps: ExpressionSolution {
+                homographs: chain.into_iter().collect(),
+            },
+        })
+    }
+
+    /// Words with the right length which may be used as steps, one for each spelling
+    fn candidates<'a>(
+        &self,
+        dict: &'a WordContext,
+        start: &str,
+        end: &str,
+    ) -> HashMap<String, &'a Homograph> {
+        dict.term_dict
+            .homographs
+            .iter()
+            .filter(|h| {
+                h.text.len() == start.len() && h.text.chars().all(|c| c.is_ascii_alphabetic())
+            })
+            .map(|h| (h.text.to_ascii_lowercase(), h))
+            .filter(|(text, h)| {
+                text == start || text == end || self.steps.as_ref().is_none_or(|q| q.allow(h))
+            })
+            .unique_by(|(text, _)| text.clone())
+            .collect()
+    }
+}
+
+/// Words grouped by their spelling with one letter replaced by a wildcard, so that c?t contains cat and cot
+struct NeighbourIndex<'a> {
+    words: &'a HashMap<String, &'a Homograph>,
+    buckets: HashMap<String, Vec<&'a str>>,
+}
+
+impl<'a> NeighbourIndex<'a> {
+    fn new(words: &'a HashMap<String, &'a Homograph>) -> Self {
+        let mut buckets: HashMap<String, Vec<&'a str>> = HashMap::new();
+        for text in words.keys() {
+            for bucket in Self::buckets(text) {
+                buckets.entry(bucket).or_default().push(text);
+            }
+        }
+        for bucket in buckets.values_mut() {
+            bucket.sort_unstable();
+        }
+
+        Self { words, buckets }
+    }
+
+    fn buckets(text: &str) -> impl Iterator<Item = String> + '_ {
+        (0..text.len()).map(|i| format!("{}?{}", &text[..i], &text[i + 1..]))
+    }
+
+    fn neighbours(&self, text: &'a str) -> impl Iterator<Item = &'a str> + '_ {
+        Self::buckets(text)
+            .filter_map(|bucket| self.buckets.get(&bucket))
+            .flatten()
+            .copied()
+            .filter(move |n| *n != text)
+    }
+
+    /// Search outwards from the start, recording every way of reaching each word in the fewest steps
+    fn shortest_chains(&self, start: &str, end: &str) -> Vec<Vec<Homograph>> {
+        let (Some((start, _)), Some((end, _))) = (
+            self.words.get_key_value(start),
+            self.words.get_key_value(end),
+        ) else {
+            return vec![];
+        };
+
+        let mut distances: HashMap<&str, usize> = HashMap::from([(start.as_str(), 0)]);
+        let mut parents: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut queue = VecDeque::from([start.as_str()]);
+
+        while let Some(word) = queue.pop_front() {
+            let distance = distances[word];
+            if distances.get(end.as_str()).is_some_and(|d| *d <= distance) {
+                break;
+            }
+
+            for neighbour in self.neighbours(word) {
+                match distances.get(neighbour) {
+                    None => {
+                        distances.insert(neighbour, distance + 1);
+                        parents.insert(neighbour, vec![word]);
+                        queue.push_back(neighbour);
+                    }
+                    Some(d) if *d == distance + 1 => {
+                        parents.entry(neighbour).or_default().push(word)
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        let mut chains = vec![];
+        if distances.contains_key(end.as_str()) {
+            self.collect_chains(end, &parents, &mut vec![], &mut chains);
+        }
+        chains.sort();
+        chains
+            .into_iter()
+            .map(|chain| {
+                chain
+                    .into_iter()
+                    .map(|text| self.words[text].clone())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn collect_chains(
+        &self,
+        word: &'a str,
+        parents: &HashMap<&str, Vec<&'a str>>,
+        suffix: &mut Vec<&'a str>,
+        chains: &mut Vec<Vec<&'a str>>,
+    ) {
+        suffix.push(word);
+        match parents.get(word) {
+            Some(previous) => {
+                for p in previous {
+                    self.collect_chains(p, parents, suffix, chains);
+                }
+            }
+            None => chains.push(suffix.iter().rev().copied().collect()),
+        }
+        suffix.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::core::prelude::*;
+    use crate::language::prelude::*;
+    use ntest::test_case;
+
+    const WORDS: &str = "n\tcat\t\t\nn\tcot\t\t\nn\tcog\t\t\nn\tdog\t\t\nn\tdot\t\t\nn\tcar\t\t\nv\tcog\t\t\nv\tdig\t\t\n";
+
+    #[test_case(
+        "ladder(cat, dog)",
+        "cat → cot → cog → dog; cat → cot → dot → dog",
+        name = "two_chains"
+    )]
+    #[test_case(
+        "ladder(cat, dog, -cog)",
+        "cat → cot → dot → dog",
+        name = "steps_filtered"
+    )]
+    #[test_case("ladder(cat, car)", "cat → car", name = "one_step")]
+    #[test_case("ladder(cat, dig, #v)", "", name = "no_chain")]
+    #[test_case("ladder(cat, cart)", "", name = "different_lengths")]
+    fn test_ladder(input: &str, expected: &str) {
+        let dict = WordContext::from(TermDict::from_csv(WORDS).unwrap());
+        let question = question_parse(input).unwrap();
+
+        let actual = question.solve(&dict).map(|s| s.get_text()).join("; ");
+
+        assert_eq!(actual, expected);
+    }
+}
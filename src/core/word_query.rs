@@ -1,4 +1,4 @@
-use std::borrow::Borrow;
+use std::{borrow::Borrow, fmt::Display};
 
 use auto_enums::auto_enum;
 use itertools::Itertools;
@@ -46,6 +46,12 @@ impl WordQueryDisjunction {
             t.upgrade_literals(dict)
         }
     }
+
+    pub fn bind_source_letters(&mut self, letters: &str) {
+        for t in self.terms.iter_mut() {
+            t.bind_source_letters(letters)
+        }
+    }
 }
 
 impl From<WordQueryTerm> for WordQueryDisjunction {
@@ -64,16 +70,76 @@ pub enum Multiword {
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum WordQueryTerm {
     Literal(Homograph),
+    Rack(Box<Rack>),
     PartOfSpeech(PartOfSpeech),
     Tag(WordTag),
+    /// Words which have an emoji
+    Emoji,
     Any,
-    Range { min: usize, max: usize },
+    Range {
+        min: usize,
+        max: usize,
+    },
+    Syllables {
+        min: usize,
+        max: usize,
+    },
+    Gematria(Gematria),
     Length(usize),
     Pattern(Pattern),
+    Regex(RawRegex),
+    Contains(ContainsLetters),
+    Rhymes(Rhyme),
+    Related(RelatedWords),
+    Category(Category),
+    Definition(DefinitionSearch),
     Nested(Box<WordQuery>),
+    Not(Box<WordQueryTerm>),
+}
+
+impl Display for WordQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.terms.iter().join(" + "))
+    }
+}
+
+impl Display for WordQueryDisjunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.terms.iter().join(" / "))
+    }
+}
+
+impl Display for WordQueryTerm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WordQueryTerm::Literal(l) => write!(f, "{}", l.text),
+            WordQueryTerm::Rack(r) => write!(f, "{r}"),
+            WordQueryTerm::PartOfSpeech(pos) => write!(f, "#{}", pos.short_name()),
+            WordQueryTerm::Tag(tag) => write!(f, "#{}", tag.name()),
+            WordQueryTerm::Emoji => write!(f, "#{}", WordQueryTerm::EMOJI),
+            WordQueryTerm::Any => write!(f, "*"),
+            WordQueryTerm::Range { min, max } => write!(f, "{min}..{max}"),
+            WordQueryTerm::Syllables { min, max } if min == max => write!(f, "syl:{min}"),
+            WordQueryTerm::Syllables { min, max } => write!(f, "syl:{min}..{max}"),
+            WordQueryTerm::Gematria(g) => write!(f, "{g}"),
+            WordQueryTerm::Length(len) => write!(f, "{len}"),
+            WordQueryTerm::Pattern(p) => write!(f, "{p}"),
+            WordQueryTerm::Regex(r) => write!(f, "{r}"),
+            WordQueryTerm::Contains(c) => write!(f, "{c}"),
+            WordQueryTerm::Rhymes(r) => write!(f, "{r}"),
+            WordQueryTerm::Related(r) => write!(f, "{r}"),
+            WordQueryTerm::Category(c) => write!(f, "{c}"),
+            WordQueryTerm::Definition(d) => write!(f, "{d}"),
+            WordQueryTerm::Nested(n) => write!(f, "({n})"),
+            WordQueryTerm::Not(n) => write!(f, "-{n}"),
+        }
+    }
 }
 
 impl WordQueryTerm {
+    /// The tag for words which have an emoji
+    pub const EMOJI: &'static str = "emoji";
+
     pub fn upgrade_literals(&mut self, dict: &WordContext) {
         match self {
             WordQueryTerm::Literal(l) => {
@@ -84,6 +150,20 @@ impl WordQueryTerm {
                 }
             }
             WordQueryTerm::Nested(n) => n.upgrade_literals(dict),
+            WordQueryTerm::Not(n) => n.upgrade_literals(dict),
+            WordQueryTerm::Rhymes(r) => r.upgrade(dict),
+            WordQueryTerm::Related(r) => r.upgrade(dict),
+            WordQueryTerm::Category(c) => c.upgrade(dict),
+            WordQueryTerm::Definition(d) => d.upgrade(dict),
+            _ => (),
+        }
+    }
+
+    pub fn bind_source_letters(&mut self, letters: &str) {
+        match self {
+            WordQueryTerm::Pattern(p) => p.bind_source_letters(letters),
+            WordQueryTerm::Nested(n) => n.bind_source_letters(letters),
+            WordQueryTerm::Not(n) => n.bind_source_letters(letters),
             _ => (),
         }
     }
@@ -103,6 +183,11 @@ impl WordQuery {
             return term.solve(dict);
         }
 
+        //Keep the order of ranked terms
+        if let Some(ranked) = self.terms.iter().find(|t| t.is_ranked()) {
+            return ranked.solve(dict).filter(|t| self.allow(t));
+        }
+
         return dict.homographs.iter().filter(|t| self.allow(t));
         //let result = dict.homographs.iter().filter(|t| self.terms.iter().all(|r|r.allow(t)));
         //result
@@ -112,6 +197,19 @@ impl WordQuery {
         // return filtered;
     }
 
+    /// Like `solve`, but a rack stands for its own letters rather than the words it can form
+    #[auto_enum(Iterator, Clone)]
+    pub fn solve_as_letters<'a>(
+        &'a self,
+        dict: &'a TermDict,
+    ) -> impl Iterator<Item = &'a Homograph> + 'a + Clone {
+        if let Some(rack) = self.as_rack() {
+            std::iter::once(&rack.homograph)
+        } else {
+            self.solve(dict)
+        }
+    }
+
     pub fn allow(&self, term: &Homograph) -> bool {
         self.terms.iter().all(|t| t.allow(term))
     }
@@ -123,14 +221,22 @@ impl WordQuery {
         None
     }
 
+    pub fn as_rack(&self) -> Option<&Rack> {
+        if let Ok(term) = self.terms.iter().exactly_one() {
+            return term.as_rack();
+        }
+        None
+    }
+
     pub fn is_any(&self) -> bool {
         self.terms
             .iter()
             .all(|x| x.terms.iter().any(|x| matches!(x, WordQueryTerm::Any)))
     }
 
+    /// The number of options for this query as one side of an equation, where a rack counts once
     pub fn count_options(&self, dict: &WordContext) -> usize {
-        self.solve(&dict.term_dict).count()
+        self.solve_as_letters(&dict.term_dict).count()
     }
 
     pub fn upgrade_literals(&mut self, dict: &WordContext) {
@@ -138,6 +244,12 @@ impl WordQuery {
             t.upgrade_literals(dict)
         }
     }
+
+    pub fn bind_source_letters(&mut self, letters: &str) {
+        for t in self.terms.iter_mut() {
+            t.bind_source_letters(letters)
+        }
+    }
 }
 
 impl WordQueryDisjunction {
@@ -145,6 +257,17 @@ impl WordQueryDisjunction {
         self.terms.iter().any(|t| t.allow(term))
     }
 
+    /// Whether this solves to words in order of relevance rather than dictionary order
+    pub fn is_ranked(&self) -> bool {
+        matches!(
+            self.terms.iter().exactly_one(),
+            Ok(WordQueryTerm::Definition(DefinitionSearch {
+                matches: Some(_),
+                ..
+            }))
+        )
+    }
+
     pub fn as_literal(&self) -> Option<&Homograph> {
         if let Ok(term) = self.terms.iter().exactly_one() {
             return term.as_literal();
@@ -152,6 +275,13 @@ impl WordQueryDisjunction {
         None
     }
 
+    pub fn as_rack(&self) -> Option<&Rack> {
+        if let Ok(term) = self.terms.iter().exactly_one() {
+            return term.as_rack();
+        }
+        None
+    }
+
     #[auto_enum(Iterator, Clone)]
     pub fn solve<'a>(
         &'a self,
@@ -189,14 +319,27 @@ impl WordQueryTerm {
             WordQueryTerm::Literal(l) => return std::iter::once(l),
             WordQueryTerm::PartOfSpeech(pos) => dict.homographs_by_part_of_speech[pos].iter(),
             WordQueryTerm::Any => dict.homographs.iter(),
+            WordQueryTerm::Definition(DefinitionSearch {
+                matches: Some(matches),
+                ..
+            }) => matches.iter().map(|i| &dict.homographs[*i]),
             //WordQueryTerm::Nested(n) => n.solve(dict), - using this causes a compilation error
             _ => dict.homographs.iter().filter(|t| self.allow(t)),
         }
     }
 
+    /// The word this term stands for, if it is a literal word or a rack of letters
     pub fn as_literal(&self) -> Option<&Homograph> {
         match self {
             WordQueryTerm::Literal(h) => Some(h),
+            WordQueryTerm::Rack(r) => Some(&r.homograph),
+            _ => None,
+        }
+    }
+
+    pub fn as_rack(&self) -> Option<&Rack> {
+        match self {
+            WordQueryTerm::Rack(r) => Some(r),
             _ => None,
         }
     }
@@ -204,15 +347,168 @@ impl WordQueryTerm {
     pub fn allow(&self, term: &Homograph) -> bool {
         match self {
             WordQueryTerm::Literal(l) => term.text.eq_ignore_ascii_case(&l.text),
+            WordQueryTerm::Rack(r) => r.allow(term),
             WordQueryTerm::Any => true,
             WordQueryTerm::Range { min, max } => term.text.len() >= *min && term.text.len() <= *max,
+            WordQueryTerm::Syllables { min, max } => (*min..=*max).contains(&term.syllables()),
+            WordQueryTerm::Gematria(g) => g.allow(term),
             WordQueryTerm::Length(len) => term.text.len() == *len,
             WordQueryTerm::Pattern(p) => p.allow(term),
+            WordQueryTerm::Regex(r) => r.allow(term),
+            WordQueryTerm::Contains(c) => c.allow(term),
+            WordQueryTerm::Rhymes(r) => r.allow(term),
+            WordQueryTerm::Related(r) => r.allow(term),
+            WordQueryTerm::Category(c) => c.allow(term),
+            WordQueryTerm::Definition(d) => d.allow(term),
             WordQueryTerm::PartOfSpeech(pos) => {
                 term.meanings.iter().any(|m| m.part_of_speech == *pos)
             }
             WordQueryTerm::Tag(tag) => term.meanings.iter().any(|m| m.tags.contains(*tag)),
+            WordQueryTerm::Emoji => term.emoji().is_some(),
             WordQueryTerm::Nested(nested) => nested.allow(term),
+            WordQueryTerm::Not(negated) => !negated.allow(term),
+        }
+    }
+
+    /// Whether a phrase as a whole satisfies this term.
+    /// Syllables and gematria use the total over all words, other terms must be satisfied by some word.
+    pub fn allow_phrase(&self, solution: &ExpressionSolution) -> bool {
+        match self {
+            WordQueryTerm::Syllables { min, max } => {
+                (*min..=*max).contains(&solution.homographs.iter().map(|h| h.syllables()).sum())
+            }
+            WordQueryTerm::Gematria(g) => g.allow_solution(solution),
+            _ => solution.homographs.iter().any(|h| self.allow(h)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::core::prelude::*;
+    use crate::language::prelude::*;
+    use ntest::test_case;
+
+    const WORDS: &str = "n\tcat\t\t\nn\tcot\t\t\nf\tTed\t\tmasculine\nn\tted\t\t\nv\tcut\t\t\n";
+
+    fn parse_word_query(input: &str) -> WordQuery {
+        match question_parse(input).unwrap() {
+            Question::Expression(Expression::FixedLength(mut fle)) => fle.words.remove(0),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test_case("-#f", "cat cot ted cut", name = "not_first_name")]
+    #[test_case("c?t + -cot", "cat cut", name = "not_literal")]
+    #[test_case("-c?t / #v", "Ted ted cut", name = "disjunction")]
+    #[test_case("(#n + -c*) / #f", "Ted ted", name = "nested")]
+    #[test_case("- -#f", "Ted", name = "double_negation")]
+    fn test_negation(input: &str, expected: &str) {
+        let dict = TermDict::from_csv(WORDS).unwrap();
+        let query = parse_word_query(input);
+
+        let actual = query.solve(&dict).map(|h| h.text.clone()).join(" ");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test_case("/^c.t$/", "cat cot cut", name = "anchored")]
+    #[test_case("/^t/", "Ted ted", name = "case_insensitive")]
+    #[test_case("#n + /[^a]t$/", "cot", name = "conjunction")]
+    #[test_case("cat/cot/ted", "cat cot Ted ted", name = "disjunction_not_regex")]
+    #[test_case("(/^cu/ / #f)", "Ted cut", name = "regex_in_disjunction")]
+    fn test_regex(input: &str, expected: &str) {
+        let dict = TermDict::from_csv(WORDS).unwrap();
+        let query = parse_word_query(input);
+
+        let actual = query.solve(&dict).map(|h| h.text.clone()).join(" ");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test_case("~t", "cat cot Ted ted cut", name = "contains_letter")]
+    #[test_case("~tc", "cat cot cut", name = "contains_letters")]
+    #[test_case("~tt", "", name = "contains_repeated_letter")]
+    #[test_case("~ta[c]", "cat", name = "contains_with_extras")]
+    #[test_case("~ct[ao]", "cat cot", name = "contains_with_several_extras")]
+    #[test_case("~ct[^u]", "cat cot", name = "contains_with_negated_extras")]
+    #[test_case("~ct[]", "", name = "contains_exactly")]
+    #[test_case("~tac[]", "cat", name = "contains_exactly_match")]
+    fn test_contains(input: &str, expected: &str) {
+        let dict = TermDict::from_csv(WORDS).unwrap();
+        let query = parse_word_query(input);
+
+        let actual = query.solve(&dict).map(|h| h.text.clone()).join(" ");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test_case("/[a/", 0, 4, name = "unclosed_class")]
+    #[test_case("#n + /a{2,1}/", 5, 13, name = "bad_repetition")]
+    fn test_invalid_regex(input: &str, start: usize, end: usize) {
+        let error = question_parse(input).unwrap_err();
+
+        assert_eq!(error.span, start..end);
+    }
+
+    #[test_case("** + -#f", "cat ted", true, name = "no_first_names")]
+    #[test_case("** + -#f", "cat Ted", false, name = "one_first_name")]
+    #[test_case("** + #f + -cat", "cot Ted", true, name = "mixed_terms")]
+    fn test_negation_in_many_expression(input: &str, text: &str, expected: bool) {
+        let dict = TermDict::from_csv(WORDS).unwrap();
+        let expression = match question_parse(input).unwrap() {
+            Question::Expression(e) => e,
+            _ => unreachable!(),
+        };
+
+        let homographs = text
+            .split_ascii_whitespace()
+            .map(|word| {
+                dict.homographs
+                    .iter()
+                    .find(|h| h.text == word)
+                    .unwrap()
+                    .clone()
+            })
+            .collect();
+
+        let solution = ExpressionSolution { homographs };
+
+        assert_eq!(expression.allow(&solution), expected);
+    }
+
+    const EMOJI: &str = "n\tcat\t\t\t\t🐈\nn\tsun\t\t\t\t☀️\nn\tmat\t\t\t\t\nv\tsat\t\t\t\t\n";
+
+    #[test_case("#emoji", "cat sun", name = "has_emoji")]
+    #[test_case("#n + -#emoji", "mat", name = "no_emoji")]
+    #[test_case("#EMOJI + ?at", "cat", name = "upper_case_emoji")]
+    fn test_emoji(input: &str, expected: &str) {
+        let dict = TermDict::from_csv(EMOJI).unwrap();
+        let query = parse_word_query(input);
+
+        let actual = query.solve(&dict).map(|h| h.text.clone()).join(" ");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test_case("cat sat mat", "🐈 sat mat", name = "one_emoji")]
+    #[test_case("sun cat", "☀️ 🐈", name = "all_emoji")]
+    #[test_case("dog", "dog", name = "unknown_word")]
+    fn test_rebus(input: &str, expected: &str) {
+        let dict = WordContext::from(TermDict::from_csv(EMOJI).unwrap());
+        let mut question = question_parse(input).unwrap();
+        question.upgrade_literals(&dict);
+
+        let actual = question
+            .solve(&dict)
+            .map(|s| match s {
+                QuestionSolution::Expression(e) => e.get_rebus(),
+                _ => unreachable!(),
+            })
+            .join("; ");
+
+        assert_eq!(actual, expected);
+    }
+}
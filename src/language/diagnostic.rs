@@ -0,0 +1,255 @@
+use std::{fmt::Display, ops::Range};
+
+use itertools::Itertools;
+use pest::error::{ErrorVariant, InputLocation};
+
+use crate::core::prelude::*;
+use crate::language::prelude::*;
+
+/// A problem found while parsing a question, in a form that can be shown to the user
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The byte range of the input that the problem relates to
+    pub span: Range<usize>,
+    /// The grammar rules that would have been accepted at the start of the span
+    pub expected: Vec<Rule>,
+    /// A short description of the problem
+    pub message: String,
+    /// Possible corrections for the text in the span
+    pub suggestions: Vec<String>,
+}
+
+impl Diagnostic {
+    const MAX_SUGGESTIONS: usize = 5;
+
+    pub fn new(error: pest_consume::Error<Rule>, input: &str) -> Self {
+        let (span, text) = match error.location {
+            InputLocation::Pos(pos) => {
+                let end = input[pos..]
+                    .chars()
+                    .next()
+                    .map(|c| pos + c.len_utf8())
+                    .unwrap_or(pos);
+                let token_end = input[pos..]
+                    .find(|c: char| c.is_whitespace() || "()+/".contains(c))
+                    .map(|i| pos + i)
+                    .unwrap_or(input.len());
+                (pos..end, &input[pos..token_end])
+            }
+            InputLocation::Span((start, end)) => (start..end, &input[start..end]),
+        };
+
+        let (expected, message) = match error.variant {
+            ErrorVariant::ParsingError {
+                positives,
+                negatives,
+            } => {
+                let message = if !positives.is_empty() {
+                    format!("Expected {}", Self::describe_rules(&positives))
+                } else if !negatives.is_empty() {
+                    format!("Unexpected {}", Self::describe_rules(&negatives))
+                } else if span.is_empty() {
+                    "Unexpected end of input".to_string()
+                } else {
+                    "Unexpected input".to_string()
+                };
+                (positives, message)
+            }
+            ErrorVariant::CustomError { message } => (vec![], message),
+        };
+
+        Diagnostic {
+            span,
+            expected,
+            message,
+            suggestions: Self::suggest(text),
+        }
+    }
+
+    /// The line of the input containing the problem, with the problem underlined
+    pub fn underline(&self, input: &str) -> String {
+        let (before, marked, _) = self.split(input);
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = input[before.len()..]
+            .find('\n')
+            .map(|i| i + before.len())
+            .unwrap_or(input.len());
+
+        let padding = before[line_start..].chars().count();
+        let width = marked.lines().next().unwrap_or_default().chars().count();
+
+        format!(
+            "{}\n{}{}",
+            &input[line_start..line_end],
+            " ".repeat(padding),
+            "^".repeat(width.max(1))
+        )
+    }
+
+    /// Split the input into the text before, within and after the span
+    pub fn split<'a>(&self, input: &'a str) -> (&'a str, &'a str, &'a str) {
+        let start = self.span.start.min(input.len());
+        let end = self.span.end.clamp(start, input.len());
+
+        (&input[..start], &input[start..end], &input[end..])
+    }
+
+    fn describe_rules(rules: &[Rule]) -> String {
+        rules
+            .iter()
+            .map(|r| Self::describe_rule(*r))
+            .unique()
+            .join(" or ")
+    }
+
+    fn describe_rule(rule: Rule) -> String {
+        match rule {
+            Rule::EOI => "end of input",
+            Rule::file | Rule::question => "a question",
+            Rule::statement => "a question or definition",
+            Rule::definition => "a definition",
+            Rule::keyword_let => "let",
+            Rule::variable | Rule::variable_name => "a variable such as $name",
+            Rule::equation => "an equation",
+            Rule::ladder => "a word ladder such as ladder(cat, dog)",
+            Rule::expression | Rule::many_expression | Rule::fixed_length_expression => {
+                "an expression"
+            }
+            Rule::word_query_conjunction | Rule::word_query_disjunction | Rule::query_term => {
+                "a word query"
+            }
+            Rule::bracketed_conjunction => "a bracketed word query",
+            Rule::negation => "a negated term",
+            Rule::contains => "letters to contain such as ~are",
+            Rule::rack | Rule::rack_letters => "a rack of letters such as [retain??]",
+            Rule::regex | Rule::regex_body => "a regular expression",
+            Rule::literal => "a word",
+            Rule::question_marks | Rule::question_mark => "?",
+            Rule::letter_variable => "a letter variable such as [1]",
+            Rule::tag => "a tag such as #n",
+            Rule::rhymes => "a rhyme such as #rhymes(moon)",
+            Rule::related | Rule::relation => "related words such as #syn(happy)",
+            Rule::category => "a category such as #is(animal)",
+            Rule::definition_search
+            | Rule::definition_clauses
+            | Rule::definition_clause
+            | Rule::quoted_text
+            | Rule::definition_word => "a definition search such as \"small rodent\"",
+            Rule::syllables => "a syllable count such as syl:2",
+            Rule::phrase_constraints | Rule::phrase_constraint => {
+                "a phrase constraint such as (syl:5)"
+            }
+            Rule::gematria | Rule::cipher_name | Rule::cipher_table | Rule::letter_value => {
+                "a letter value total such as $=74"
+            }
+            Rule::pattern => "a pattern",
+            Rule::quantified | Rule::quantifier => "a quantifier such as {2,3}",
+            Rule::many_term | Rule::many_any | Rule::many_tag => "** or !phrase or !palindrome",
+            Rule::any => "*",
+            Rule::character_class | Rule::character_set => "a character class such as @v",
+            Rule::length => "a number",
+            Rule::range => "a range such as 3..5",
+            Rule::equality_operator => "an equality operator such as =a",
+            _ => return format!("{rule:?}").replace('_', " "),
+        }
+        .to_string()
+    }
+
+    /// Suggest known tags and symbols that are similar to the text
+    fn suggest(text: &str) -> Vec<String> {
+        let text = text.to_ascii_lowercase();
+
+        let candidates = match text.chars().next() {
+            Some('#') => PartOfSpeech::NAMES
+                .iter()
+                .map(|(name, _)| name)
+                .chain(WordTag::NAMES.iter().map(|(name, _)| name))
+                .chain(Relation::NAMES.iter().map(|(name, _)| name))
+                .chain([WordQueryTerm::EMOJI, Rhyme::NAME, Category::NAME].iter())
+                .map(|name| format!("#{name}"))
+                .collect_vec(),
+            Some('=') => EqualityOperator::examples().collect_vec(),
+            Some('@') => CharacterClass::SYMBOLS
+                .iter()
+                .map(|(symbol, _)| symbol.to_string())
+                .collect_vec(),
+            Some('!') => ManyExpressionType::SYMBOLS
+                .iter()
+                .map(|(symbol, _)| symbol)
+                .filter(|symbol| symbol.starts_with('!'))
+                .map(|symbol| symbol.to_string())
+                .collect_vec(),
+            _ => return vec![],
+        };
+
+        let max_distance = (text.chars().count() / 2).max(1);
+
+        candidates
+            .into_iter()
+            .filter(|c| *c != text)
+            .map(|c| (edit_distance(&text, &c), c))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min_set_by_key(|(distance, _)| *distance)
+            .into_iter()
+            .map(|(_, c)| c)
+            .take(Self::MAX_SUGGESTIONS)
+            .collect_vec()
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)?;
+        if !self.suggestions.is_empty() {
+            write!(
+                f,
+                " - did you mean {}?",
+                self.suggestions.iter().join(" or ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::language::prelude::*;
+    use ntest::test_case;
+
+    #[test_case("#nuon", 0, 5, "#noun", name = "tag")]
+    #[test_case("cat =x *", 4, 6, "=a =s =b =r =h", name = "equality_operator")]
+    #[test_case("cat =n *", 4, 6, "=a =s =b =r =h", name = "no_placeholder")]
+    #[test_case("c@xt", 1, 3, "@v @c @y @u @s", name = "character_class")]
+    #[test_case("!phrse", 0, 6, "!phrase", name = "many_tag")]
+    #[test_case("#n + #zzzzzz", 5, 12, "", name = "no_suggestion")]
+    #[test_case("#rhyms(moon)", 0, 6, "#rhymes", name = "rhymes")]
+    #[test_case("#synn(happy)", 0, 5, "#syn", name = "relation")]
+    #[test_case("#emoj", 0, 5, "#emoji", name = "emoji")]
+    fn test_suggestions(input: &str, start: usize, end: usize, expected: &str) {
+        let diagnostic = question_parse(input).unwrap_err();
+
+        assert_eq!(diagnostic.span, start..end);
+        assert_eq!(diagnostic.suggestions.join(" "), expected);
+    }
+
+    #[test]
+    fn test_expected_rules() {
+        let diagnostic = question_parse("cat + ").unwrap_err();
+
+        assert_eq!(diagnostic.span, 6..6);
+        assert!(diagnostic.expected.contains(&Rule::query_term));
+        assert_eq!(diagnostic.message, "Expected a word query");
+    }
+
+    #[test]
+    fn test_underline() {
+        let input = "5 + #nuon";
+        let diagnostic = question_parse(input).unwrap_err();
+
+        assert_eq!(diagnostic.underline(input), "5 + #nuon\n    ^^^^^");
+        assert_eq!(
+            diagnostic.to_string(),
+            "Not a valid tag - did you mean #noun?"
+        );
+    }
+}
@@ -30,6 +30,32 @@ impl Example {
             Example::make("#j hero =a #f #l", "Name my character!"),
             Example::make("n?u?h?y", "Cheat at crosswords"),
             Example::make("5 + c???t + *e*", "Cheat at wordle"),
+            Example::make("5 + -*e*", "Avoid the letter e"),
+            Example::make("/^[^aeiou]+y$/", "Y is the only vowel"),
+            Example::make("@u@u@u@u@u + #n", "Nouns with five different letters"),
+            Example::make("[1][2][2][1]", "Words shaped like abba"),
+            Example::make("#n + b@v{2}@c+", "Nouns with a double vowel after b"),
+            Example::make("5 + ~are[st]", "Rack letters with at least a, r and e"),
+            Example::make("countdown =b *", "Longest words from these letters"),
+            Example::make("[retain??] =a *", "Scrabble rack with two blanks"),
+            Example::make("crushing blow =s * *", "Spoonerisms"),
+            Example::make("#n =r *", "Nouns which spell another word backwards"),
+            Example::make("capital on donkey =h 6", "Words hidden in a cryptic clue"),
+            Example::make("ladder(cold, warm, #n / #j)", "Word ladder through nouns and adjectives"),
+            Example::make("#n =+1 #n", "Transadditions: a noun plus one letter"),
+            Example::make("#n + $=74", "Nouns with a gematria value of 74"),
+            Example::make("gematria =g #j #n", "Phrases with the same gematria value"),
+            Example::make("let us pray =p #n", "Puns on a phrase"),
+            Example::make("!palindrome + #n", "Phrases which read the same both ways"),
+            Example::make("\"small rodent\"", "Reverse dictionary"),
+            Example::make("6 + def:\"capital city\"", "Crossword answers from a definition"),
+            Example::make("#syn(happy)", "Thesaurus"),
+            Example::make("#is(animal) + 3", "Three letter animals"),
+            Example::make("#j =a #syn(happy)", "Adjectives which are anagrams of synonyms of happy"),
+            Example::make("#emoji + #n + 3", "Short nouns with an emoji"),
+            Example::make("#rhymes(orange)", "Words which rhyme with orange"),
+            Example::make("!phrase(syl:5)", "A phrase for the first line of a haiku"),
+            Example::make("let short = (#n + 3..4); $short $short", "Reuse a definition"),
         ]
     }
 }
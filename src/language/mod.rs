@@ -1,6 +1,8 @@
+mod diagnostic;
 mod examples;
 mod word_lang_parser;
 pub mod prelude {
+    pub use crate::language::diagnostic::*;
     pub use crate::language::examples::*;
     pub use crate::language::word_lang_parser::*;
 }
@@ -1,6 +1,10 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
 use std::str::FromStr;
 
 use crate::core::prelude::*;
+use crate::language::prelude::Diagnostic;
 
 use itertools::Itertools;
 use pest_consume::{match_nodes, Error, Parser};
@@ -9,9 +13,17 @@ use pest_consume::{match_nodes, Error, Parser};
 #[grammar = "language/wordlang.pest"]
 pub struct WordLangParser;
 
-type Node<'i> = pest_consume::Node<'i, Rule, ()>;
+type Node<'i> = pest_consume::Node<'i, Rule, Rc<Bindings>>;
 type Result<T> = std::result::Result<T, Error<Rule>>;
 
+/// The names defined so far in a script, in the order the parser meets them
+#[derive(Debug, Default)]
+pub struct Bindings {
+    definitions: RefCell<HashMap<String, WordQuery>>,
+    /// The name whose definition is currently being parsed
+    defining: RefCell<Option<String>>,
+}
+
 #[pest_consume::parser]
 impl WordLangParser {
     fn EOI(_input: Node) -> Result<()> {
@@ -30,18 +42,74 @@ impl WordLangParser {
     }
 
     fn many_tag(input: Node) -> Result<ManyExpressionType> {
-        if input.as_str().to_ascii_lowercase() == "!phrase" {
-            Ok(ManyExpressionType::Phrase)
-        } else {
-            Err(input.error("Not a many expression"))
-        }
+        ManyExpressionType::from_str(input.as_str()).map_err(|e| input.error(e))
     }
 
     fn many_expression(input: Node) -> Result<ManyExpression> {
         Ok(match_nodes!(input.into_children();
-            [many_term(t), query_term(terms)..] => ManyExpression { t, terms: terms.collect(), min_words: 1, max_words: None },
+            [many_term(t), phrase_constraints(constraints), query_term(terms)..] => ManyExpression { t, terms: terms.collect(), min_words: 1, max_words: None, constraints },
+        ))
+    }
+
+    fn phrase_constraints(input: Node) -> Result<Vec<WordQueryTerm>> {
+        Ok(match_nodes!(input.into_children();
+            [phrase_constraint(constraints)..] => constraints.collect(),
+        ))
+    }
+
+    fn phrase_constraint(input: Node) -> Result<WordQueryTerm> {
+        Ok(match_nodes!(input.into_children();
+            [syllables(x)] => x,
+            [gematria(x)] => x,
         ))
     }
+
+    fn syllables(input: Node) -> Result<WordQueryTerm> {
+        Ok(match_nodes!(input.into_children();
+            [length(n)] => WordQueryTerm::Syllables { min: n, max: n },
+            [length(min), length(max)] => WordQueryTerm::Syllables { min, max },
+        ))
+    }
+
+    /// A total letter value such as `$=74`, `$reverse:50..60` or `$(a=1 e=1 z=10)=12`
+    fn gematria(input: Node) -> Result<WordQueryTerm> {
+        let (cipher, min, max) = match_nodes!(input.clone().into_children();
+            [length(n)] => (Cipher::Simple, n, n),
+            [length(min), length(max)] => (Cipher::Simple, min, max),
+            [cipher_name(c), length(n)] => (c, n, n),
+            [cipher_name(c), length(min), length(max)] => (c, min, max),
+            [cipher_table(c), length(n)] => (c, n, n),
+            [cipher_table(c), length(min), length(max)] => (c, min, max),
+        );
+        let min = u32::try_from(min).map_err(|e| input.error(e))?;
+        let max = u32::try_from(max).map_err(|e| input.error(e))?;
+
+        Ok(WordQueryTerm::Gematria(Gematria { cipher, min, max }))
+    }
+
+    fn cipher_name(input: Node) -> Result<Cipher> {
+        Cipher::from_str(input.as_str()).map_err(|e| input.error(e))
+    }
+
+    fn cipher_table(input: Node) -> Result<Cipher> {
+        let mut values = [0; 26];
+        for (letter, value) in match_nodes!(input.into_children();
+            [letter_value(v)..] => v,
+        ) {
+            values[letter.ordinal() as usize - 1] = value;
+        }
+
+        Ok(Cipher::Custom(values.into()))
+    }
+
+    fn letter_value(input: Node) -> Result<(Character, u32)> {
+        let text = input.as_str();
+        let letter = Character::try_from(text.chars().next().unwrap_or_default())
+            .map_err(|e| input.error(e))?;
+        let value = u32::from_str(&text[2..]).map_err(|e| input.error(e))?;
+
+        Ok((letter, value))
+    }
     fn range(input: Node) -> Result<WordQueryTerm> {
         Ok(match_nodes!(input.into_children();
             [length(min), length(max)] => WordQueryTerm::Range{min, max},
@@ -62,6 +130,10 @@ impl WordLangParser {
         if let Ok(wordtag) = WordTag::from_str(lit) {
             return Ok(WordQueryTerm::Tag(wordtag));
         }
+
+        if lit.eq_ignore_ascii_case(WordQueryTerm::EMOJI) {
+            return Ok(WordQueryTerm::Emoji);
+        }
         Err(input.error("Not a valid tag"))
     }
     fn bracketed_conjunction(input: Node) -> Result<WordQueryTerm> {
@@ -73,22 +145,168 @@ impl WordLangParser {
         Ok(input.as_str().to_string())
     }
 
+    fn variable_name(input: Node) -> Result<String> {
+        Ok(input.as_str().to_string())
+    }
+
+    fn variable(input: Node) -> Result<WordQueryTerm> {
+        let bindings = input.user_data().clone();
+        let name = match_nodes!(input.children();
+            [variable_name(name)] => name,
+        );
+
+        if bindings.defining.borrow().as_ref() == Some(&name) {
+            return Err(input.error(format!("Recursive definition of ${name}")));
+        }
+
+        let query = bindings.definitions.borrow().get(&name).cloned();
+        match query {
+            //A single term stands for itself, so it can still be a literal or a rack
+            Some(query) => Ok(query.into()),
+            None => Err(input.error(format!("${name} is not defined"))),
+        }
+    }
+
+    fn negation(input: Node) -> Result<WordQueryTerm> {
+        Ok(match_nodes!(input.into_children();
+            [query_term(x)] => WordQueryTerm::Not(Box::new(x)),
+        ))
+    }
+
     fn any(input: Node) -> Result<WordQueryTerm> {
         Ok(WordQueryTerm::Any)
     }
 
     fn pattern(input: Node) -> Result<Pattern> {
+        let span = input.as_span();
         let components: Vec<PatternComponent> = input
             .into_pair()
             .into_inner()
             .map(PatternComponent::try_parse)
             .try_collect()?;
 
-        Ok(components.into())
+        Pattern::try_from(components).map_err(|e| {
+            Error::new_from_span(
+                pest::error::ErrorVariant::CustomError {
+                    message: e.to_string(),
+                },
+                span,
+            )
+        })
+    }
+
+    fn regex(input: Node) -> Result<RawRegex> {
+        let text = input.as_str();
+        let source = text[1..text.len() - 1].replace("\\/", "/");
+
+        RawRegex::from_str(source.as_str()).map_err(|e| input.error(e))
+    }
+
+    fn character_set(input: Node) -> Result<CharacterClass> {
+        match &input.as_str()[1..] {
+            "]" => Ok(CharacterClass::Set {
+                letters: String::new(),
+                negated: false,
+            }),
+            set => CharacterClass::parse_set(set).map_err(|e| input.error(e)),
+        }
+    }
+
+    fn contains(input: Node) -> Result<ContainsLetters> {
+        Ok(match_nodes!(input.children();
+            [literal(letters), character_set(extras)..] => ContainsLetters {
+                required: AnagramKey::from_str(letters.as_str()).map_err(|e| input.error(e))?,
+                extras: extras.last(),
+            },
+        ))
+    }
+
+    fn rack_letters(input: Node) -> Result<String> {
+        Ok(input.as_str().to_string())
+    }
+
+    /// Letters to use as an anagram source, where `?` is a blank
+    fn rack(input: Node) -> Result<WordQueryTerm> {
+        Ok(match_nodes!(input.clone().into_children();
+            [rack_letters(text)] => WordQueryTerm::Rack(Box::new(
+                Rack::from_str(&text).map_err(|e| input.error(e))?
+            )),
+        ))
+    }
+
+    fn rhymes(input: Node) -> Result<WordQueryTerm> {
+        Ok(match_nodes!(input.into_children();
+            [literal(word)] => WordQueryTerm::Rhymes(Rhyme::new(word)),
+        ))
+    }
+
+    /// A search of definitions such as `"small rodent"` or `def:(rodent & small)`
+    fn definition_search(input: Node) -> Result<WordQueryTerm> {
+        let clauses = match_nodes!(input.into_children();
+            [definition_clause(clause)] => vec![clause],
+            [definition_clauses(clauses)] => clauses,
+        );
+        Ok(WordQueryTerm::Definition(DefinitionSearch::new(clauses)))
+    }
+
+    fn definition_clauses(input: Node) -> Result<Vec<Vec<String>>> {
+        Ok(match_nodes!(input.into_children();
+            [definition_clause(clauses)..] => clauses.collect(),
+        ))
+    }
+
+    fn definition_clause(input: Node) -> Result<Vec<String>> {
+        let text = match_nodes!(input.clone().into_children();
+            [quoted_text(text)] => text,
+            [definition_word(word)] => word,
+        );
+        let tokens = DefinitionIndex::tokens(&text).unique().collect_vec();
+
+        if tokens.is_empty() {
+            return Err(input.error("Search for at least one word that is not too common"));
+        }
+        Ok(tokens)
+    }
+
+    fn quoted_text(input: Node) -> Result<String> {
+        Ok(input.as_str().to_string())
+    }
+
+    fn definition_word(input: Node) -> Result<String> {
+        Ok(input.as_str().to_string())
+    }
+
+    /// Words related in the thesaurus such as `#syn(happy)`
+    fn related(input: Node) -> Result<WordQueryTerm> {
+        Ok(match_nodes!(input.into_children();
+            [relation(relation), literal(word)] => WordQueryTerm::Related(RelatedWords::new(relation, word)),
+        ))
+    }
+
+    fn relation(input: Node) -> Result<Relation> {
+        Relation::from_str(input.as_str()).map_err(|e| input.error(e))
+    }
+
+    /// Every kind of something such as `#is(animal)`
+    fn category(input: Node) -> Result<WordQueryTerm> {
+        Ok(match_nodes!(input.into_children();
+            [literal(word)] => WordQueryTerm::Category(Category::new(word)),
+        ))
     }
 
     fn query_term(input: Node) -> Result<WordQueryTerm> {
         Ok(match_nodes!(input.into_children();
+            [negation(x)] =>x ,
+            [regex(x)] =>WordQueryTerm::Regex(x),
+            [contains(x)] =>WordQueryTerm::Contains(x),
+            [rack(x)] =>x ,
+            [rhymes(x)] =>x ,
+            [related(x)] =>x ,
+            [category(x)] =>x ,
+            [definition_search(x)] =>x ,
+            [syllables(x)] =>x ,
+            [gematria(x)] =>x ,
+            [variable(x)] =>x ,
             [pattern(x)] =>WordQueryTerm::Pattern(x),
             [literal(text)] => WordQueryTerm::Literal(Homograph {
                 text: text.into(),
@@ -129,36 +347,117 @@ impl WordLangParser {
     }
 
     fn equality_operator(input: Node) -> Result<EqualityOperator> {
-        match input.as_str().to_ascii_lowercase().as_str() {
-            "=a" => Ok(EqualityOperator::Anagram),
-            "=s" => Ok(EqualityOperator::Spoonerism),
-            _ => Err(input.error("Could not parse as equality operator")),
-        }
+        match_nodes!(input.clone().into_children();
+            [cipher_name(c)] => Ok(EqualityOperator::Gematria(c)),
+            [cipher_table(c)] => Ok(EqualityOperator::Gematria(c)),
+            [] => EqualityOperator::from_str(input.as_str()).map_err(|e| input.error(e)),
+        )
     }
 
     fn equation(input: Node) -> Result<Equation> {
         Ok(match_nodes!(input.into_children();
-            [expression(left), equality_operator(operator), expression(right)] => Equation{left, operator, right},
+            [expression(left), equality_operator(operator), expression(right)] => {
+                let mut equation = Equation{left, operator, right};
+                equation.bind_source_letters();
+                equation
+            },
+        ))
+    }
+
+    fn ladder(input: Node) -> Result<WordLadder> {
+        Ok(match_nodes!(input.into_children();
+            [literal(start), literal(end)] => WordLadder { start, end, steps: None },
+            [literal(start), literal(end), word_query_conjunction(steps)] => WordLadder { start, end, steps: Some(steps) },
         ))
     }
 
     fn question(input: Node) -> Result<Question> {
         Ok(match_nodes!(input.into_children();
-            [equation(eq)] => Question::Equation(eq),
+            [ladder(l)] => Question::Ladder(l),
+            [equation(eq)] => Question::Equation(Box::new(eq)),
             [expression(e)] => Question::Expression(e),
         ))
     }
 
-    fn file(input: Node) -> Result<Question> {
+    fn keyword_let(input: Node) -> Result<()> {
+        Ok(())
+    }
+
+    fn definition(input: Node) -> Result<()> {
+        let bindings = input.user_data().clone();
+        let mut children = input.children();
+        let (Some(name_node), Some(query_node)) = (
+            children.find(|x| x.as_rule() == Rule::variable_name),
+            children.next(),
+        ) else {
+            return Err(input.error("Could not parse definition"));
+        };
+        let name = Self::variable_name(name_node)?;
+
+        if bindings.definitions.borrow().contains_key(&name) {
+            return Err(input.error(format!("${name} is already defined")));
+        }
+
+        bindings.defining.replace(Some(name.clone()));
+        let query = Self::word_query_conjunction(query_node);
+        bindings.defining.replace(None);
+
+        bindings.definitions.borrow_mut().insert(name, query?);
+        Ok(())
+    }
+
+    fn statement(input: Node) -> Result<Option<Question>> {
         Ok(match_nodes!(input.into_children();
-            [question(q), _] => q,
+            [definition(_)] => None,
+            [question(q)] => Some(q),
         ))
     }
+
+    fn file(input: Node) -> Result<Vec<Question>> {
+        let statements = match_nodes!(input.into_children();
+            [statement(s).., _] => s.collect_vec(),
+        );
+        let is_single = statements.len() == 1;
+
+        //Empty statements are only meaningful when they are the whole file
+        Ok(statements
+            .into_iter()
+            .flatten()
+            .filter(|q| is_single || !q.is_empty())
+            .collect_vec())
+    }
+}
+
+/// Parse a file containing exactly one question
+pub fn question_parse(input_str: &str) -> std::result::Result<Question, Diagnostic> {
+    let mut questions = script_parse(input_str)?;
+
+    if questions.len() == 1 {
+        Ok(questions.remove(0))
+    } else {
+        let message = if questions.is_empty() {
+            "Expected a question"
+        } else {
+            "Expected a single question"
+        };
+        Err(Diagnostic {
+            span: 0..input_str.len(),
+            expected: vec![Rule::question],
+            message: message.to_string(),
+            suggestions: vec![],
+        })
+    }
 }
 
-pub fn question_parse(input_str: &str) -> Result<Question> {
+/// Parse a script of definitions and questions, separated by semicolons
+pub fn script_parse(input_str: &str) -> std::result::Result<Vec<Question>, Diagnostic> {
+    parse_file(input_str).map_err(|error| Diagnostic::new(error, input_str))
+}
+
+fn parse_file(input_str: &str) -> Result<Vec<Question>> {
     // Parse the input into `Nodes`
-    let inputs = WordLangParser::parse(Rule::file, input_str)?;
+    let inputs =
+        WordLangParser::parse_with_userdata(Rule::file, input_str, Rc::new(Bindings::default()))?;
     // There should be a single root node in the parsed tree
     let input = inputs.single()?;
     // Consume the `Node` recursively into the final value
@@ -169,8 +468,44 @@ impl PatternComponent {
     fn try_parse(pair: pest::iterators::Pair<Rule>) -> Result<Self> {
         match pair.as_rule() {
             Rule::question_marks => Ok(PatternComponent::AnyChar(pair.as_str().len())),
+            Rule::question_mark => Ok(PatternComponent::AnyChar(1)),
             Rule::any => Ok(PatternComponent::Any),
             Rule::literal => Ok(PatternComponent::Literal(pair.as_str().to_string())),
+            Rule::quantified => {
+                let span = pair.as_span();
+                let mut inner = pair.into_inner();
+                let (Some(component), Some(quantifier)) = (inner.next(), inner.next()) else {
+                    unreachable!()
+                };
+                let component = Box::new(PatternComponent::try_parse(component)?);
+                let (min, max) = Self::parse_quantifier(quantifier.as_str()).map_err(|x| {
+                    Error::new_from_span(
+                        pest::error::ErrorVariant::CustomError {
+                            message: x.to_string(),
+                        },
+                        span,
+                    )
+                })?;
+
+                Ok(PatternComponent::Repeat {
+                    component,
+                    min,
+                    max,
+                })
+            }
+            Rule::letter_variable => {
+                let text = pair.as_str();
+                usize::from_str(&text[1..text.len() - 1])
+                    .map(PatternComponent::Variable)
+                    .map_err(|x| {
+                        Error::new_from_span(
+                            pest::error::ErrorVariant::CustomError {
+                                message: x.to_string(),
+                            },
+                            pair.as_span(),
+                        )
+                    })
+            }
             Rule::character_class => Ok(PatternComponent::CharacterClass(
                 CharacterClass::from_str(pair.as_str()).map_err(|x| {
                     Error::new_from_span(
@@ -184,4 +519,119 @@ impl PatternComponent {
             _ => unreachable!(),
         }
     }
+
+    /// The minimum and maximum number of repetitions of a quantifier such as `+` or `{2,3}`
+    fn parse_quantifier(text: &str) -> anyhow::Result<(usize, Option<usize>)> {
+        if text == "+" {
+            return Ok((1, None));
+        }
+
+        let inner = &text[1..text.len() - 1];
+        let (min, max) = match inner.split_once(',') {
+            None => {
+                let n = usize::from_str(inner)?;
+                (n, Some(n))
+            }
+            Some((min, "")) => (usize::from_str(min)?, None),
+            Some((min, max)) => (usize::from_str(min)?, Some(usize::from_str(max)?)),
+        };
+
+        match max {
+            Some(0) => anyhow::bail!("The quantifier {text} does not allow any repetitions"),
+            Some(max) if min > max => {
+                anyhow::bail!("The quantifier {text} has a minimum greater than its maximum")
+            }
+            _ => Ok((min, max)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::prelude::*;
+    use crate::language::prelude::*;
+    use itertools::Itertools;
+    use ntest::test_case;
+
+    #[test]
+    fn test_definitions() {
+        let questions = script_parse(
+            "let animal = (#n + 3..6);\nlet nice_name = #f + #positive;\n$animal; $nice_name 5",
+        )
+        .unwrap();
+
+        let texts = questions.iter().map(|q| q.to_string()).collect::<Vec<_>>();
+        assert_eq!(texts, vec!["(#n + 3..6)", "(#f + #positive) 5"]);
+    }
+
+    #[test_case("[retain?] =a *", "let w = [retain?]; $w =a *", name = "rack")]
+    #[test_case("tca =a *", "let w = tca; $w =a *", name = "unknown_literal")]
+    #[test_case("cat =b *", "let w = cat; $w =b *", name = "sub_anagram")]
+    fn test_definition_is_inline(inline: &str, defined: &str) {
+        let dict = WordContext::from(
+            TermDict::from_csv("n\tact\t\t\nn\tcat\t\t\nn\tcertain\t\t\nn\tretrain\t\t\n").unwrap(),
+        );
+        let solve = |input: &str| {
+            let mut question = question_parse(input).unwrap();
+            question.upgrade_literals(&dict);
+            let text = question.solve(&dict).map(|s| s.get_text()).join("; ");
+            text
+        };
+
+        let expected = solve(inline);
+
+        assert!(!expected.is_empty());
+        assert_eq!(solve(defined), expected);
+    }
+
+    #[test]
+    fn test_definition_display() {
+        let question = question_parse("let x = 3; let y = $x + #n; $y").unwrap();
+
+        assert_eq!(question.to_string(), "(3 + #n)");
+    }
+
+    #[test]
+    fn test_equation_is_not_definition() {
+        let question = question_parse("let me =a tel em").unwrap();
+
+        assert!(matches!(question, Question::Equation(_)));
+    }
+
+    #[test_case("$animal", 0, 7, "$animal is not defined", name = "undefined")]
+    #[test_case(
+        "let a = 3 + $a; $a",
+        12,
+        14,
+        "Recursive definition of $a",
+        name = "recursive"
+    )]
+    #[test_case(
+        "let a = 3; let a = 4; $a",
+        11,
+        20,
+        "$a is already defined",
+        name = "redefined"
+    )]
+    #[test_case("let a = 3;", 0, 10, "Expected a question", name = "no_question")]
+    #[test_case(
+        "cat; dog",
+        0,
+        8,
+        "Expected a single question",
+        name = "many_questions"
+    )]
+    #[test_case(
+        "cat =+4 *",
+        4,
+        7,
+        "Can edit at most 3 letters",
+        name = "too_many_edits"
+    )]
+    fn test_definition_errors(input: &str, start: usize, end: usize, message: &str) {
+        let diagnostic = question_parse(input).unwrap_err();
+
+        assert_eq!(diagnostic.message, message);
+        assert_eq!(diagnostic.span, start..end);
+    }
 }
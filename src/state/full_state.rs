@@ -1,4 +1,3 @@
-use std::borrow::Borrow;
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -17,25 +16,38 @@ use yewdux::storage;
 #[derive(Clone, Serialize, Deserialize)]
 pub struct FullState {
     pub text: String,
+    /// Show expressions as emoji rebuses
+    #[serde(default)]
+    pub rebus: bool,
     #[serde(skip)]
     pub hot: bool,
     #[serde(skip)]
     pub is_complete: bool,
     #[serde(skip)]
-    pub data: Vec<QuestionSolution>,
+    pub results: Vec<QuestionResults>,
 
     #[serde(skip)]
     pub question: Option<Question>,
     pub warning: Option<String>,
-
     #[serde(skip)]
-    pub iter: Option<Rc<RefCell<dyn Iterator<Item = QuestionSolution>>>>,
+    pub diagnostic: Option<Diagnostic>,
+}
+
+/// The solutions found so far for one question of the script
+#[derive(Clone)]
+pub struct QuestionResults {
+    /// The question as written back out
+    pub text: String,
+    pub data: Vec<QuestionSolution>,
+    pub is_complete: bool,
+    pub iter: Rc<RefCell<dyn Iterator<Item = QuestionSolution>>>,
 }
 
 impl PartialEq for FullState {
     fn eq(&self, other: &Self) -> bool {
         self.text == other.text
-            && self.data.len() == other.data.len()
+            && self.rebus == other.rebus
+            && self.count_solutions() == other.count_solutions()
             && self.is_complete == other.is_complete
             && self.hot == other.hot
     }
@@ -51,12 +63,13 @@ impl Default for FullState {
     fn default() -> Self {
         Self {
             text: "hello world =a !phrase".into(),
+            rebus: false,
             hot: true,
             is_complete: true,
             question: None,
-            data: Default::default(),
+            results: Default::default(),
             warning: Default::default(),
-            iter: Default::default(),
+            diagnostic: Default::default(),
         }
     }
 }
@@ -94,6 +107,11 @@ impl Store for FullState {
 }
 
 impl FullState {
+    /// The number of solutions found so far for all questions
+    pub fn count_solutions(&self) -> usize {
+        self.results.iter().map(|r| r.data.len()).sum()
+    }
+
     pub fn info_text(&self) -> Cow<'static, str> {
         if let Some(w) = &self.warning {
             return w.clone().into();
@@ -104,68 +122,78 @@ impl FullState {
         }
 
         if self.is_complete {
-            format!("Found all {} solutions", self.data.len()).into()
+            format!("Found all {} solutions", self.count_solutions()).into()
         } else {
-            format!("Found {} solutions", self.data.len()).into()
+            format!("Found {} solutions", self.count_solutions()).into()
         }
     }
 
+    /// Load up to `number_to_load` more solutions for each question which is not complete
     pub fn load_more(&mut self, number_to_load: usize) {
         if self.is_complete {
             return;
         }
-        if let Some(iter) = self.iter.borrow() {
-            let mut i = 0;
-            let start_instant = instant::Instant::now();
-
-            let mut iter_borrow = iter.as_ref().borrow_mut();
-
+        let start_instant = instant::Instant::now();
 
+        for results in self.results.iter_mut().filter(|r| !r.is_complete) {
+            let mut i = 0;
+            let mut iter_borrow = results.iter.as_ref().borrow_mut();
 
             while let Some(s) = iter_borrow.next() {
-                self.data.push(s);
+                results.data.push(s);
                 i += 1;
                 if i >= number_to_load {
                     break;
                 }
             }
             if i < number_to_load {
-                self.is_complete = true;
+                results.is_complete = true;
             }
             debug!(
-                "Found {} solutions ({} total) in {:?}",
+                "Found {} solutions ({} total) for {} in {:?}",
                 i,
-                self.data.len(),
+                results.data.len(),
+                results.text,
                 start_instant.elapsed()
             );
         }
+
+        self.is_complete = self.results.iter().all(|r| r.is_complete);
     }
 
     fn update(&mut self) {
-        let r = question_parse(&self.text);
+        let r = script_parse(&self.text);
         match r {
-            Ok(mut question) => {
+            Ok(mut questions) => {
+                self.diagnostic = None;
                 let solve_context = get_solve_context();
-                if question.is_too_difficult(solve_context) {
-                    self.data.clear();
-                    self.iter = None;
+                if questions.iter().any(|q| q.is_too_difficult(solve_context)) {
+                    self.results.clear();
                     self.warning = Some("Question is too difficult".to_string());
                     self.is_complete = false;
                 } else {
-                    question.upgrade_literals(solve_context);
-                    let qq = Box::leak(Box::new(question));
-                    let iter = qq.solve(solve_context);
-
-                    self.data.clear();
-                    self.iter = Some(Rc::new(RefCell::new(iter)));
+                    for question in questions.iter_mut() {
+                        question.upgrade_literals(solve_context);
+                    }
+                    let qq: &'static Vec<Question> = Box::leak(Box::new(questions));
+
+                    self.results = qq
+                        .iter()
+                        .map(|q| QuestionResults {
+                            text: q.to_string(),
+                            data: Default::default(),
+                            is_complete: false,
+                            iter: Rc::new(RefCell::new(q.solve(solve_context))),
+                        })
+                        .collect();
                     self.warning = Default::default();
                     self.is_complete = false;
                 }
             }
-            Err(warning) => {
-                self.data.clear();
-                self.iter = None;
-                self.warning = Some(warning.to_string());
+            Err(diagnostic) => {
+                self.results.clear();
+                self.warning = Some(diagnostic.to_string());
+                self.diagnostic = Some(diagnostic);
                 self.is_complete = false;
             }
         }
@@ -173,7 +201,7 @@ impl FullState {
 
     pub fn update_if_hot(&mut self) {
         if self.hot {
-            self.iter = None;
+            self.results.clear();
             self.update();
             self.load_more(10);
             self.hot = false;
@@ -4,7 +4,7 @@ use crate::state::prelude::*;
 use itertools::Itertools;
 
 use shrewd_orca::language::prelude::Example;
-use web_sys::{HtmlSelectElement, HtmlTextAreaElement};
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
 use yew::prelude::*;
 use yew_hooks::*;
 use yewdux::prelude::*;
@@ -17,6 +17,7 @@ pub fn app() -> Html {
 
         <Examples />
         <InputBox />
+        <RebusToggle />
         // <RowLoader/>
         <ErrorBox />
         <DisplayBox/>
@@ -47,10 +48,43 @@ pub fn input_box() -> Html {
     )
 }
 
+#[function_component(RebusToggle)]
+pub fn rebus_toggle() -> Html {
+    let rebus = use_selector(|state: &FullState| state.rebus);
+
+    let onchange = Dispatch::<FullState>::new().reduce_mut_callback_with(|s, e: Event| {
+        let input: HtmlInputElement = e.target_unchecked_into();
+        s.rebus = input.checked();
+    });
+
+    html!(
+        <label>
+            <input type="checkbox" role="switch" checked={*rebus} {onchange}/>
+            {"Emoji rebus"}
+        </label>
+    )
+}
+
 #[function_component(ErrorBox)]
 pub fn error_box() -> Html {
     let err = use_selector(|s: &FullState| s.info_text());
-    html!(<code> {err} </code>)
+    let location = use_selector(|s: &FullState| {
+        s.diagnostic.as_ref().map(|d| {
+            let (before, marked, after) = d.split(&s.text);
+            (before.to_string(), marked.to_string(), after.to_string())
+        })
+    });
+
+    match location.as_ref() {
+        Some((before, marked, after)) => html!(
+            <code>
+                {before}<u style="text-decoration-color: red;">{marked}{"\u{a0}"}</u>{after}
+                <br/>
+                {err}
+            </code>
+        ),
+        None => html!(<code> {err} </code>),
+    }
 }
 
 #[function_component(DisplayBox)]
@@ -65,7 +99,24 @@ pub fn display_box() -> Html {
         })
     });
 
-    let rows = state.data.iter().map(row).collect_vec();
+    //Each question of a script gets its own group of rows, headed by the question
+    let show_headers = state.results.len() > 1;
+    let rows = state
+        .results
+        .iter()
+        .flat_map(|results| {
+            let header = show_headers.then(|| {
+                html!(
+                    <tr>
+                        <th colspan="3">{results.text.clone()}</th>
+                    </tr>
+                )
+            });
+            header
+                .into_iter()
+                .chain(results.data.iter().map(|x| row(x, state.rebus)))
+        })
+        .collect_vec();
 
     html!(
         <>
@@ -110,8 +161,15 @@ pub fn examples_dropdown() -> Html {
     )
 }
 
-pub fn row(solution: &QuestionSolution) -> Html {
+pub fn row(solution: &QuestionSolution, rebus: bool) -> Html {
     match solution {
+        QuestionSolution::Expression(expression) if rebus => {
+            html!(
+                <tr>
+                    <td>{expression.get_rebus()}</td>
+                </tr>
+            )
+        }
         QuestionSolution::Expression(expression) => {
             let spans = expression
                 .homographs
@@ -138,32 +196,171 @@ pub fn row(solution: &QuestionSolution) -> Html {
                 .iter()
                 .map(|x| homograph_display(x, "left"))
                 .collect_vec();
+            let blanks = if anagram.blanks.is_empty() {
+                String::new()
+            } else {
+                format!("? = {}", anagram.blanks.letters())
+            };
 
             html!(
                 <tr>
                     <td>{left_spans}</td>
                     <td>{right_spans}</td>
+                    <td>{blanks}</td>
                 </tr>
             )
         }
-        QuestionSolution::Spoonerism(spoonerism) => {
-            let left_spans = spoonerism
+        QuestionSolution::Spoonerism(SpoonerismSolution { left, right })
+        | QuestionSolution::Reversal(ReversalSolution { left, right }) => {
+            let left_spans = left
+                .homographs
+                .iter()
+                .map(|x| homograph_display(x, "right"))
+                .collect_vec();
+            let right_spans = right
+                .homographs
+                .iter()
+                .map(|x| homograph_display(x, "left"))
+                .collect_vec();
+
+            html!(
+                <tr>
+                    <td>{left_spans}</td>
+                    <td>{right_spans}</td>
+                </tr>
+            )
+        }
+        QuestionSolution::SubAnagram(sub_anagram) => {
+            let left_spans = sub_anagram
                 .left
                 .homographs
                 .iter()
                 .map(|x| homograph_display(x, "right"))
                 .collect_vec();
-            let right_spans = spoonerism
+            let right_spans = sub_anagram
                 .right
                 .homographs
                 .iter()
                 .map(|x| homograph_display(x, "left"))
                 .collect_vec();
+            let remainder = if sub_anagram.remainder.is_empty() {
+                String::new()
+            } else {
+                sub_anagram.remainder.letters()
+            };
 
             html!(
                 <tr>
                     <td>{left_spans}</td>
                     <td>{right_spans}</td>
+                    <td>{remainder}</td>
+                </tr>
+            )
+        }
+        QuestionSolution::Hidden(hidden) => {
+            let left_spans = hidden
+                .left
+                .homographs
+                .iter()
+                .map(|x| homograph_display(x, "right"))
+                .collect_vec();
+            let right_spans = hidden
+                .right
+                .homographs
+                .iter()
+                .map(|x| homograph_display(x, "left"))
+                .collect_vec();
+            let reversed = if hidden.reversed { "reversed" } else { "" };
+
+            html!(
+                <tr>
+                    <td>{left_spans}</td>
+                    <td>{right_spans}</td>
+                    <td>{reversed}</td>
+                </tr>
+            )
+        }
+        QuestionSolution::Edit(edit) => {
+            let left_spans = edit
+                .left
+                .homographs
+                .iter()
+                .map(|x| homograph_display(x, "right"))
+                .collect_vec();
+            let right_spans = edit
+                .right
+                .homographs
+                .iter()
+                .map(|x| homograph_display(x, "left"))
+                .collect_vec();
+
+            html!(
+                <tr>
+                    <td>{left_spans}</td>
+                    <td>{right_spans}</td>
+                    <td>{edit.describe_edit()}</td>
+                </tr>
+            )
+        }
+        QuestionSolution::Gematria(gematria) => {
+            let left_spans = gematria
+                .left
+                .homographs
+                .iter()
+                .map(|x| homograph_display(x, "right"))
+                .collect_vec();
+            let right_spans = gematria
+                .right
+                .homographs
+                .iter()
+                .map(|x| homograph_display(x, "left"))
+                .collect_vec();
+
+            html!(
+                <tr>
+                    <td>{left_spans}</td>
+                    <td>{right_spans}</td>
+                    <td>{gematria.value}</td>
+                </tr>
+            )
+        }
+        QuestionSolution::Pun(pun) => {
+            let left_spans = pun
+                .left
+                .homographs
+                .iter()
+                .map(|x| homograph_display(x, "right"))
+                .collect_vec();
+            let right_spans = pun
+                .right
+                .homographs
+                .iter()
+                .map(|x| homograph_display(x, "left"))
+                .collect_vec();
+
+            html!(
+                <tr>
+                    <td>{left_spans}</td>
+                    <td>{right_spans}</td>
+                    <td>{pun.describe_swap()}</td>
+                </tr>
+            )
+        }
+        QuestionSolution::Ladder(ladder) => {
+            let spans = ladder
+                .steps
+                .homographs
+                .iter()
+                .enumerate()
+                .map(|(i, x)| {
+                    let arrow = if i == 0 { "" } else { "→ " };
+                    html!(<>{arrow}{homograph_display(x, "right")}</>)
+                })
+                .collect_vec();
+
+            html!(
+                <tr>
+                    <td>{spans}</td>
                 </tr>
             )
         }
@@ -171,7 +368,10 @@ pub fn row(solution: &QuestionSolution) -> Html {
 }
 
 fn homograph_display(homograph: &Homograph, tooltip_placement: &'static str) -> Html {
-    let text = homograph.text.to_string() + " ";
+    let text = match homograph.emoji() {
+        Some(emoji) => format!("{} {} ", homograph.text, emoji),
+        None => homograph.text.to_string() + " ",
+    };
     let definition = homograph.first_definition();
 
     html!(
@@ -53,7 +53,7 @@ fn test_order_to_allow(query: &str, text: &str, expected: &str) {
     let q = question_parse(query).unwrap();
     let expression = match q {
         Question::Expression(e) => e,
-        Question::Equation(_) => unreachable!(),
+        _ => unreachable!(),
     };
 
     let homographs = SmallVec::from_vec(
@@ -83,3 +83,59 @@ fn test_order_to_allow(query: &str, text: &str, expected: &str) {
 //     assert_eq!(PHRASEEXPRESSIONS[0],any_exp );
 //     assert_eq!(PHRASEEXPRESSIONS[1],any_exp );
 // }
+
+#[test]
+fn test_examples_round_trip() {
+    for example in Example::list() {
+        assert_round_trip(&example.text);
+    }
+}
+
+#[test_case("*")]
+#[test_case("")]
+#[test_case("#noun + #Masculine")]
+#[test_case("#interjection")]
+#[test_case("c??t + @v*")]
+#[test_case("(world / earth) + -(#j / 3..4)")]
+#[test_case("/^[^aeiou]+y$/ / /x\\/y/ cat")]
+#[test_case("** + -#f + genius")]
+#[test_case("red bat =s !phrase")]
+#[test_case("countdown =b 5..9")]
+#[test_case("[retain??] =a !phrase")]
+#[test_case("let pet = cat / dog; $pet + -~t")]
+#[test_case("@[^aeiou]@y[1]?{2,3}@c+ *[1]")]
+#[test_case("~are[st] / ~are[] / ~are")]
+#[test_case("5 + #rhymes(moon) =a *")]
+#[test_case("stressed =r **")]
+#[test_case("capital on donkey =h #n + 6")]
+#[test_case("stop =hr *")]
+#[test_case("ladder(cat, dog)")]
+#[test_case("#n =+1 #n")]
+#[test_case("7 =-2 *")]
+#[test_case("cat =~1 #n")]
+#[test_case("#n + $=74 / $reverse:50..60")]
+#[test_case("!phrase($(a=1 e=1 z=10)=12)(syl:3) + #n")]
+#[test_case("gematria =g #j #n")]
+#[test_case("let us pray =p #n")]
+#[test_case("#emoji + #n")]
+#[test_case("#syn(happy) + 4 / #ant(sad)")]
+#[test_case("astute =a #sim(clever)")]
+#[test_case("#is(tool) + 6 / #is(animal)")]
+#[test_case("\"small rodent\" + 5..7")]
+#[test_case("def:(rodent & \"small animal\") / #j")]
+#[test_case("ladder(cold, warm, #n / #j)")]
+#[test_case("!palindrome + #n + -~z")]
+#[test_case("syl:2 + #n / syl:1..3")]
+#[test_case("!phrase(syl:5..7) + #n")]
+fn test_round_trip(input: &str) {
+    assert_round_trip(input);
+}
+
+fn assert_round_trip(input: &str) {
+    let question = question_parse(input).unwrap();
+    let text = question.to_string();
+    let reparsed = question_parse(&text).unwrap();
+
+    assert_eq!(question, reparsed, "'{input}' was printed as '{text}'");
+    assert_eq!(text, reparsed.to_string());
+}